@@ -0,0 +1,301 @@
+//! Microphone Capture
+//!
+//! Records from an input device via cpal. A dedicated worker thread owns the stream
+//! (cpal streams are not `Send`) and converts the device signal to 16 kHz mono PCM
+//! while recording, so the buffer is ready for the pipeline the moment capture stops.
+
+use std::collections::BTreeSet;
+use std::sync::{mpsc, Arc, Mutex};
+use std::thread::JoinHandle;
+use std::time::{Duration, Instant};
+
+use cpal::traits::{DeviceTrait, HostTrait, StreamTrait};
+use cpal::{FromSample, SampleFormat, SizedSample};
+use serde::Serialize;
+
+use super::level::{AudioLevel, LevelMeter};
+use super::{resample::StreamResampler, AudioBuffer, TARGET_SAMPLE_RATE};
+
+/// How often the worker moves captured audio into the shared buffer
+const POLL_INTERVAL: Duration = Duration::from_millis(20);
+
+/// Common rates reported when a device supports a continuous range
+const STANDARD_RATES: [u32; 9] = [
+    8_000, 11_025, 16_000, 22_050, 32_000, 44_100, 48_000, 88_200, 96_000,
+];
+
+/// Input device as shown in the settings
+#[derive(Debug, Clone, Serialize)]
+pub struct InputDevice {
+    pub name: String,
+    pub is_default: bool,
+    pub sample_rates: Vec<u32>,
+}
+
+/// List all input devices of the default host
+pub fn list_devices() -> Result<Vec<InputDevice>, String> {
+    let host = cpal::default_host();
+    let default_name = host.default_input_device().and_then(|d| d.name().ok());
+    let devices = host
+        .input_devices()
+        .map_err(|e| format!("Eingabegeräte konnten nicht aufgelistet werden: {}", e))?;
+
+    Ok(devices
+        .filter_map(|device| {
+            let name = device.name().ok()?;
+            Some(InputDevice {
+                is_default: default_name.as_deref() == Some(name.as_str()),
+                sample_rates: supported_rates(&device),
+                name,
+            })
+        })
+        .collect())
+}
+
+/// Sample rates a device can capture at (range bounds plus the common rates in between)
+fn supported_rates(device: &cpal::Device) -> Vec<u32> {
+    let Ok(configs) = device.supported_input_configs() else {
+        return Vec::new();
+    };
+
+    let mut rates = BTreeSet::new();
+    for range in configs {
+        let (min, max) = (range.min_sample_rate().0, range.max_sample_rate().0);
+        rates.insert(min);
+        rates.insert(max);
+        rates.extend(STANDARD_RATES.iter().filter(|&&r| r > min && r < max));
+    }
+    rates.into_iter().collect()
+}
+
+/// Find the device by name, falling back to the system default if it is gone
+fn select_device(host: &cpal::Host, name: Option<&str>) -> Result<cpal::Device, String> {
+    if let Some(name) = name {
+        let found = host
+            .input_devices()
+            .ok()
+            .and_then(|mut devices| devices.find(|d| d.name().ok().as_deref() == Some(name)));
+        match found {
+            Some(device) => return Ok(device),
+            None => eprintln!("⚠ Input device '{}' not found. Using system default.", name),
+        }
+    }
+
+    host.default_input_device()
+        .ok_or_else(|| "Kein Mikrofon gefunden".to_string())
+}
+
+/// A running recording. Dropping the handle also ends the capture, since the worker
+/// stops as soon as the stop channel disconnects.
+pub struct Recording {
+    /// Live 16 kHz mono PCM, grows while recording
+    samples: Arc<Mutex<Vec<i16>>>,
+    stop_tx: mpsc::Sender<()>,
+    worker: JoinHandle<Result<(), String>>,
+    started: Instant,
+}
+
+impl Recording {
+    /// Open the named input device (or the system default) and start capturing.
+    /// `on_level` is called from the worker thread with each live level reading.
+    pub fn start(
+        device_name: Option<String>,
+        meter: LevelMeter,
+        on_level: impl FnMut(AudioLevel) + Send + 'static,
+    ) -> Result<Self, String> {
+        let samples = Arc::new(Mutex::new(Vec::new()));
+        let (stop_tx, stop_rx) = mpsc::channel();
+        let (ready_tx, ready_rx) = mpsc::channel();
+
+        let worker = {
+            let samples = samples.clone();
+            std::thread::spawn(move || {
+                run_capture(device_name, meter, on_level, samples, stop_rx, ready_tx)
+            })
+        };
+
+        // Wait until the stream is running (or failed to open)
+        match ready_rx.recv() {
+            Ok(Ok(device_name)) => println!("🎙️ Recording from: {}", device_name),
+            Ok(Err(e)) => {
+                let _ = worker.join();
+                return Err(e);
+            }
+            Err(_) => {
+                return Err(match worker.join() {
+                    Ok(Err(e)) => e,
+                    _ => "Aufnahme-Thread unerwartet beendet".to_string(),
+                })
+            }
+        }
+
+        Ok(Self {
+            samples,
+            stop_tx,
+            worker,
+            started: Instant::now(),
+        })
+    }
+
+    /// Time since the recording started
+    pub fn elapsed_ms(&self) -> u64 {
+        self.started.elapsed().as_millis() as u64
+    }
+
+    /// Take the audio recorded so far while capture continues
+    pub fn take_buffer(&self) -> AudioBuffer {
+        let samples = self
+            .samples
+            .lock()
+            .map(|mut samples| std::mem::take(&mut *samples))
+            .unwrap_or_default();
+        AudioBuffer::new(samples)
+    }
+
+    /// Drop everything but the most recent `len` samples (rolling buffer)
+    pub fn retain_last(&self, len: usize) {
+        if let Ok(mut samples) = self.samples.lock() {
+            let excess = samples.len().saturating_sub(len);
+            samples.drain(..excess);
+        }
+    }
+
+    /// Stop capturing and return the recorded audio
+    pub fn stop(self) -> Result<AudioBuffer, String> {
+        let _ = self.stop_tx.send(());
+        self.worker
+            .join()
+            .map_err(|_| "Aufnahme-Thread abgestürzt".to_string())??;
+
+        let samples = std::mem::take(&mut *self.samples.lock().map_err(|e| e.to_string())?);
+        Ok(AudioBuffer::new(samples))
+    }
+
+    /// Stop capturing and discard the audio
+    pub fn cancel(self) {
+        if let Err(e) = self.stop() {
+            eprintln!("⚠ Capture error on cancel: {}", e);
+        }
+    }
+}
+
+/// A running cpal stream and the channel its callback feeds
+struct InputStream {
+    stream: cpal::Stream,
+    /// Mono f32 chunks at the device rate
+    data_rx: mpsc::Receiver<Vec<f32>>,
+    sample_rate: u32,
+    device_name: String,
+}
+
+/// Worker: owns the stream and converts captured chunks until asked to stop
+fn run_capture(
+    device_name: Option<String>,
+    mut meter: LevelMeter,
+    mut on_level: impl FnMut(AudioLevel),
+    samples: Arc<Mutex<Vec<i16>>>,
+    stop_rx: mpsc::Receiver<()>,
+    ready_tx: mpsc::Sender<Result<String, String>>,
+) -> Result<(), String> {
+    let input = match open_stream(device_name.as_deref()) {
+        Ok(input) => {
+            let _ = ready_tx.send(Ok(input.device_name.clone()));
+            input
+        }
+        Err(e) => {
+            let _ = ready_tx.send(Err(e.clone()));
+            return Err(e);
+        }
+    };
+
+    let mut resampler = StreamResampler::new(input.sample_rate, TARGET_SAMPLE_RATE)?;
+    let mut converted = Vec::new();
+    let data_rx = input.data_rx;
+
+    let mut convert = |flush: bool| -> Result<(), String> {
+        while let Ok(chunk) = data_rx.try_recv() {
+            resampler.push(&chunk, &mut converted)?;
+        }
+        if flush {
+            resampler.flush(&mut converted)?;
+        }
+        let pcm: Vec<i16> = converted.drain(..).map(super::f32_to_i16).collect();
+        meter.push(&pcm, &mut on_level);
+        if let Ok(mut samples) = samples.lock() {
+            samples.extend_from_slice(&pcm);
+        }
+        Ok(())
+    };
+
+    // Run until stop is requested or the `Recording` handle is gone
+    while let Err(mpsc::RecvTimeoutError::Timeout) = stop_rx.recv_timeout(POLL_INTERVAL) {
+        convert(false)?;
+    }
+
+    // Close the device first, then take what it delivered until then
+    drop(input.stream);
+    convert(true)
+}
+
+/// Open the input device and start a stream that sends mono f32 chunks
+fn open_stream(device_name: Option<&str>) -> Result<InputStream, String> {
+    let host = cpal::default_host();
+    let device = select_device(&host, device_name)?;
+    let device_name = device
+        .name()
+        .unwrap_or_else(|_| "Unbekanntes Gerät".to_string());
+
+    let supported = device
+        .default_input_config()
+        .map_err(|e| format!("Mikrofon-Konfiguration nicht verfügbar: {}", e))?;
+    let sample_format = supported.sample_format();
+    let config: cpal::StreamConfig = supported.into();
+    let (data_tx, data_rx) = mpsc::channel();
+
+    let stream = match sample_format {
+        SampleFormat::F32 => build_stream::<f32>(&device, &config, data_tx),
+        SampleFormat::I16 => build_stream::<i16>(&device, &config, data_tx),
+        SampleFormat::U16 => build_stream::<u16>(&device, &config, data_tx),
+        SampleFormat::I32 => build_stream::<i32>(&device, &config, data_tx),
+        format => return Err(format!("Nicht unterstütztes Sample-Format: {}", format)),
+    }
+    .map_err(|e| format!("Mikrofon-Stream konnte nicht geöffnet werden: {}", e))?;
+
+    stream
+        .play()
+        .map_err(|e| format!("Aufnahme konnte nicht gestartet werden: {}", e))?;
+
+    Ok(InputStream {
+        stream,
+        data_rx,
+        sample_rate: config.sample_rate.0,
+        device_name,
+    })
+}
+
+/// Build an input stream that downmixes every callback buffer to mono f32
+fn build_stream<T>(
+    device: &cpal::Device,
+    config: &cpal::StreamConfig,
+    data_tx: mpsc::Sender<Vec<f32>>,
+) -> Result<cpal::Stream, cpal::BuildStreamError>
+where
+    T: SizedSample,
+    f32: FromSample<T>,
+{
+    let channels = config.channels.max(1) as usize;
+    device.build_input_stream(
+        config,
+        move |data: &[T], _: &cpal::InputCallbackInfo| {
+            let mono = data
+                .chunks(channels)
+                .map(|frame| {
+                    frame.iter().map(|&s| s.to_sample::<f32>()).sum::<f32>() / channels as f32
+                })
+                .collect();
+            let _ = data_tx.send(mono);
+        },
+        |e| eprintln!("⚠ Audio stream error: {}", e),
+        None,
+    )
+}
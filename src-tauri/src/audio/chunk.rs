@@ -0,0 +1,230 @@
+//! Chunk Planning
+//!
+//! Splits long recordings into bounded pieces for transcription. Cuts go into the longest
+//! speech pause near the size limit; only when someone talks without any pause is the audio
+//! cut hard, and then the next chunk repeats a short overlap so no word is lost at the seam.
+
+use std::ops::Range;
+
+use serde::{Deserialize, Serialize};
+
+use super::vad::SpeechSegment;
+use super::AudioBuffer;
+
+/// Shortest chunk limit honoured; smaller settings would only produce slivers
+const MIN_CHUNK_MS: u32 = 10_000;
+
+/// Long-recording transcription settings (persisted in the backend config)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ChunkConfig {
+    /// Recordings longer than this are split; also the upper bound for each chunk
+    pub max_chunk_ms: u32,
+    /// Audio repeated after a hard cut (no pause found)
+    pub overlap_ms: u32,
+    /// Chunks transcribed at the same time
+    pub concurrency: usize,
+}
+
+impl Default for ChunkConfig {
+    fn default() -> Self {
+        Self {
+            max_chunk_ms: 120_000,
+            overlap_ms: 1_500,
+            concurrency: 3,
+        }
+    }
+}
+
+impl ChunkConfig {
+    /// Chunk limit, clamped to at least `MIN_CHUNK_MS`
+    fn chunk_limit_ms(&self) -> u32 {
+        self.max_chunk_ms.max(MIN_CHUNK_MS)
+    }
+
+    /// Overlap, clamped to a quarter chunk so every chunk moves forward
+    fn overlap_limit_ms(&self) -> u32 {
+        self.overlap_ms.min(self.chunk_limit_ms() / 4)
+    }
+}
+
+/// One piece of a long recording
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Chunk {
+    /// Sample range in the recording
+    pub range: Range<usize>,
+    /// Starts with a repeat of the previous chunk's end (hard cut)
+    pub overlaps_previous: bool,
+}
+
+/// Whether the recording needs to be split at all
+pub fn needs_chunking(buffer: &AudioBuffer, config: &ChunkConfig) -> bool {
+    buffer.duration_ms() > config.chunk_limit_ms() as u64
+}
+
+/// Plan chunks of at most `max_chunk_ms`, cut in speech pauses wherever possible
+pub fn plan(buffer: &AudioBuffer, segments: &[SpeechSegment], config: &ChunkConfig) -> Vec<Chunk> {
+    let to_samples = |ms: u32| (ms as u64 * buffer.sample_rate as u64 / 1000) as usize;
+    let len = buffer.samples.len();
+    let max_len = to_samples(config.chunk_limit_ms());
+    let overlap = to_samples(config.overlap_limit_ms());
+
+    // Non-speech gaps, including leading and trailing silence
+    let mut pauses = Vec::with_capacity(segments.len() + 1);
+    let mut previous_end = 0;
+    for segment in segments {
+        pauses.push(previous_end..segment.start);
+        previous_end = segment.end;
+    }
+    pauses.push(previous_end..len);
+
+    let mut chunks = Vec::new();
+    let mut start = 0;
+    let mut overlaps_previous = false;
+    while len - start > max_len {
+        let limit = start + max_len;
+        // Not too early, or chunks get needlessly small
+        let earliest = start + max_len / 2;
+
+        let cut = pauses
+            .iter()
+            .filter(|pause| !pause.is_empty())
+            .map(|pause| ((pause.start + pause.end) / 2, pause.len()))
+            .filter(|&(middle, _)| middle > earliest && middle <= limit)
+            .max_by_key(|&(_, pause_len)| pause_len)
+            .map(|(middle, _)| middle);
+
+        match cut {
+            Some(cut) => {
+                chunks.push(Chunk {
+                    range: start..cut,
+                    overlaps_previous,
+                });
+                start = cut;
+                overlaps_previous = false;
+            }
+            None => {
+                chunks.push(Chunk {
+                    range: start..limit,
+                    overlaps_previous,
+                });
+                start = limit - overlap;
+                overlaps_previous = overlap > 0;
+            }
+        }
+    }
+    chunks.push(Chunk {
+        range: start..len,
+        overlaps_previous,
+    });
+    chunks
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SECOND: usize = 16_000;
+
+    fn silence(seconds: usize) -> AudioBuffer {
+        AudioBuffer::new(vec![0; seconds * SECOND])
+    }
+
+    fn speech(from_ms: usize, to_ms: usize) -> SpeechSegment {
+        SpeechSegment {
+            start: from_ms * SECOND / 1000,
+            end: to_ms * SECOND / 1000,
+        }
+    }
+
+    fn config(max_chunk_ms: u32, overlap_ms: u32) -> ChunkConfig {
+        ChunkConfig {
+            max_chunk_ms,
+            overlap_ms,
+            ..ChunkConfig::default()
+        }
+    }
+
+    #[test]
+    fn cuts_in_the_longest_pause_before_the_limit() {
+        let buffer = silence(25);
+        let segments = [
+            speech(0, 6_000),
+            speech(6_200, 7_000),
+            speech(7_800, 14_000),
+            speech(14_600, 25_000),
+        ];
+        let chunks = plan(&buffer, &segments, &config(10_000, 1_500));
+
+        // 7.0–7.8 s beats 6.0–6.2 s; then 14.0–14.6 s; the rest is one pause-less stretch
+        let ranges: Vec<_> = chunks.iter().map(|c| c.range.clone()).collect();
+        assert_eq!(
+            ranges,
+            [
+                0..7_400 * SECOND / 1000,
+                7_400 * SECOND / 1000..14_300 * SECOND / 1000,
+                14_300 * SECOND / 1000..24_300 * SECOND / 1000,
+                22_800 * SECOND / 1000..25 * SECOND,
+            ]
+        );
+        let overlaps: Vec<_> = chunks.iter().map(|c| c.overlaps_previous).collect();
+        assert_eq!(overlaps, [false, false, false, true]);
+    }
+
+    #[test]
+    fn hard_cut_repeats_the_overlap() {
+        let buffer = silence(25);
+        let chunks = plan(&buffer, &[speech(0, 25_000)], &config(10_000, 1_500));
+
+        assert_eq!(
+            chunks,
+            [
+                Chunk {
+                    range: 0..10 * SECOND,
+                    overlaps_previous: false,
+                },
+                Chunk {
+                    range: 8_500 * SECOND / 1000..18_500 * SECOND / 1000,
+                    overlaps_previous: true,
+                },
+                Chunk {
+                    range: 17 * SECOND..25 * SECOND,
+                    overlaps_previous: true,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn short_recordings_stay_whole() {
+        let buffer = silence(8);
+        assert!(!needs_chunking(&buffer, &config(10_000, 1_500)));
+        let chunks = plan(&buffer, &[speech(500, 7_500)], &config(10_000, 1_500));
+        assert_eq!(chunks.len(), 1);
+        assert_eq!(chunks[0].range, 0..8 * SECOND);
+    }
+
+    #[test]
+    fn invalid_settings_are_clamped() {
+        let buffer = silence(25);
+        let segments = [speech(0, 25_000)];
+
+        // No limit: same plan as the 10 s minimum instead of one chunk per sample
+        assert_eq!(
+            plan(&buffer, &segments, &config(0, 1_500)),
+            plan(&buffer, &segments, &config(10_000, 1_500))
+        );
+        assert!(!needs_chunking(&silence(5), &config(0, 1_500)));
+
+        // Overlap longer than a chunk: capped to a quarter, so each chunk moves forward
+        let chunks = plan(&buffer, &segments, &config(10_000, 60_000));
+        assert_eq!(chunks.len(), 3);
+        for pair in chunks.windows(2) {
+            assert_eq!(
+                pair[1].range.start,
+                pair[0].range.end - 2_500 * SECOND / 1000
+            );
+        }
+        assert_eq!(chunks.last().unwrap().range.end, buffer.samples.len());
+    }
+}
@@ -0,0 +1,257 @@
+//! Native Audio Decoder
+//!
+//! Decodes WebM/Matroska and Ogg recordings (Opus or Vorbis) as well as imported files
+//! (WAV, MP3, M4A/AAC, FLAC, audio track of MP4/MKV) into 16 kHz mono PCM S16LE
+//! without depending on an external ffmpeg binary.
+
+use std::fs::File;
+use std::io::Cursor;
+use std::path::Path;
+
+use audiopus::{coder::Decoder as OpusDecoder, packet::Packet, Channels, MutSignals, SampleRate};
+use symphonia::core::{
+    audio::SampleBuffer,
+    codecs::{CodecParameters, Decoder, DecoderOptions, CODEC_TYPE_NULL, CODEC_TYPE_OPUS},
+    errors::Error as SymphoniaError,
+    formats::{FormatOptions, Packet as ContainerPacket},
+    io::{MediaSource, MediaSourceStream},
+    meta::MetadataOptions,
+    probe::Hint,
+};
+
+use super::{f32_to_i16, resample::StreamResampler, TARGET_SAMPLE_RATE};
+
+/// Opus always decodes at 48 kHz internally
+const OPUS_SAMPLE_RATE: u32 = 48_000;
+
+/// Largest Opus frame (120 ms at 48 kHz) per channel
+const OPUS_MAX_FRAME: usize = 5760;
+
+/// Codec-specific decoder for the selected audio track
+enum TrackDecoder {
+    /// Codecs supported by symphonia (Vorbis, PCM, FLAC, ...)
+    Symphonia(Box<dyn Decoder>),
+    /// Opus via libopus (symphonia only demuxes Opus, it cannot decode it)
+    Opus {
+        decoder: OpusDecoder,
+        channels: usize,
+        pre_skip: usize,
+    },
+}
+
+impl TrackDecoder {
+    fn new(params: &CodecParameters) -> Result<Self, String> {
+        if params.codec == CODEC_TYPE_OPUS {
+            let channels = params.channels.map(|c| c.count()).unwrap_or(1);
+            let opus_channels = match channels {
+                1 => Channels::Mono,
+                2 => Channels::Stereo,
+                n => return Err(format!("Opus mit {} Kanälen wird nicht unterstützt", n)),
+            };
+            let decoder = OpusDecoder::new(SampleRate::Hz48000, opus_channels)
+                .map_err(|e| format!("Opus-Decoder Initialisierung fehlgeschlagen: {}", e))?;
+
+            // OpusHead: magic (8) | version (1) | channels (1) | pre-skip (2, LE)
+            let pre_skip = params
+                .extra_data
+                .as_deref()
+                .filter(|head| head.len() >= 12 && head.starts_with(b"OpusHead"))
+                .map(|head| u16::from_le_bytes([head[10], head[11]]) as usize)
+                .unwrap_or(0);
+
+            Ok(TrackDecoder::Opus {
+                decoder,
+                channels,
+                pre_skip,
+            })
+        } else {
+            let decoder = symphonia::default::get_codecs()
+                .make(params, &DecoderOptions::default())
+                .map_err(|e| format!("Nicht unterstützter Audio-Codec: {}", e))?;
+            Ok(TrackDecoder::Symphonia(decoder))
+        }
+    }
+
+    /// Decode one packet and append it (downmixed to mono) to `output`.
+    /// Returns the sample rate of the decoded audio.
+    fn decode(
+        &mut self,
+        packet: &ContainerPacket,
+        output: &mut Vec<f32>,
+    ) -> Result<Option<u32>, String> {
+        match self {
+            TrackDecoder::Symphonia(decoder) => {
+                let decoded = match decoder.decode(packet) {
+                    Ok(decoded) => decoded,
+                    // Corrupt packets are skipped, like ffmpeg does
+                    Err(SymphoniaError::DecodeError(_)) => return Ok(None),
+                    Err(e) => return Err(format!("Fehler beim Dekodieren: {}", e)),
+                };
+                let spec = *decoded.spec();
+                let mut buffer = SampleBuffer::<f32>::new(decoded.capacity() as u64, spec);
+                buffer.copy_interleaved_ref(decoded);
+                downmix(buffer.samples(), spec.channels.count(), output);
+                Ok(Some(spec.rate))
+            }
+            TrackDecoder::Opus {
+                decoder,
+                channels,
+                pre_skip,
+            } => {
+                let packet = Packet::try_from(&packet.data[..])
+                    .map_err(|e| format!("Ungültiges Opus-Paket: {}", e))?;
+                let mut frame = vec![0.0f32; OPUS_MAX_FRAME * *channels];
+                let signals = MutSignals::try_from(&mut frame)
+                    .map_err(|e| format!("Opus-Puffer Fehler: {}", e))?;
+                let samples = match decoder.decode_float(Some(packet), signals, false) {
+                    Ok(samples) => samples,
+                    Err(_) => return Ok(None),
+                };
+
+                // Drop the encoder priming samples at the start of the stream
+                let skip = samples.min(*pre_skip);
+                *pre_skip -= skip;
+                downmix(
+                    &frame[skip * *channels..samples * *channels],
+                    *channels,
+                    output,
+                );
+                Ok(Some(OPUS_SAMPLE_RATE))
+            }
+        }
+    }
+}
+
+/// Decode an encoded recording (WebM/Matroska or Ogg) into 16 kHz mono PCM
+pub fn decode(audio_data: &[u8]) -> Result<Vec<i16>, String> {
+    decode_source(Box::new(Cursor::new(audio_data.to_vec())), Hint::new())
+}
+
+/// Decode an audio file, or the audio track of a video file, into 16 kHz mono PCM
+pub fn decode_file(path: &Path) -> Result<Vec<i16>, String> {
+    let file = File::open(path)
+        .map_err(|e| format!("Fehler beim Öffnen von {}: {}", path.display(), e))?;
+
+    let mut hint = Hint::new();
+    if let Some(extension) = path.extension().and_then(|e| e.to_str()) {
+        hint.with_extension(extension);
+    }
+    decode_source(Box::new(file), hint)
+}
+
+/// Demux, decode and resample packet by packet, so hour-long files never exist as
+/// full-rate float audio in memory
+fn decode_source(source: Box<dyn MediaSource>, hint: Hint) -> Result<Vec<i16>, String> {
+    let source = MediaSourceStream::new(source, Default::default());
+
+    let probed = symphonia::default::get_probe()
+        .format(
+            &hint,
+            source,
+            &FormatOptions::default(),
+            &MetadataOptions::default(),
+        )
+        .map_err(|e| format!("Unbekanntes Audioformat: {}", e))?;
+    let mut format = probed.format;
+
+    // Video containers list the video track too: prefer a track that has a sample rate
+    let playable = |t: &&symphonia::core::formats::Track| t.codec_params.codec != CODEC_TYPE_NULL;
+    let track = format
+        .tracks()
+        .iter()
+        .filter(playable)
+        .find(|t| t.codec_params.sample_rate.is_some())
+        .or_else(|| format.tracks().iter().find(playable))
+        .ok_or("Keine Audiospur gefunden")?;
+    let track_id = track.id;
+    let mut decoder = TrackDecoder::new(&track.codec_params)?;
+    let track_rate = track.codec_params.sample_rate;
+
+    let mut resampler: Option<StreamResampler> = None;
+    let mut mono = Vec::new();
+    let mut resampled = Vec::new();
+    let mut samples = Vec::new();
+    loop {
+        let packet = match format.next_packet() {
+            Ok(packet) => packet,
+            // End of stream (MediaRecorder WebM has no duration, so EOF is the only marker)
+            Err(SymphoniaError::IoError(e)) if e.kind() == std::io::ErrorKind::UnexpectedEof => {
+                break
+            }
+            Err(SymphoniaError::ResetRequired) => break,
+            Err(e) => return Err(format!("Fehler beim Lesen des Containers: {}", e)),
+        };
+
+        if packet.track_id() != track_id {
+            continue;
+        }
+
+        mono.clear();
+        let Some(rate) = decoder.decode(&packet, &mut mono)? else {
+            continue;
+        };
+        let resampler = match resampler.as_mut() {
+            Some(resampler) => resampler,
+            None => resampler.insert(StreamResampler::new(rate, TARGET_SAMPLE_RATE)?),
+        };
+        resampled.clear();
+        resampler.push(&mono, &mut resampled)?;
+        samples.extend(resampled.iter().copied().map(f32_to_i16));
+    }
+
+    match resampler.as_mut() {
+        Some(resampler) => {
+            resampled.clear();
+            resampler.flush(&mut resampled)?;
+            samples.extend(resampled.iter().copied().map(f32_to_i16));
+        }
+        // No packet decoded: fine for an empty recording, but not for unknown audio
+        None if track_rate.is_none() => return Err("Unbekannte Abtastrate".to_string()),
+        None => {}
+    }
+
+    Ok(samples)
+}
+
+/// Decode via an external ffmpeg binary (optional override for exotic inputs).
+/// The recording is piped through stdin, so it never needs to exist as a file.
+pub fn decode_with_ffmpeg(ffmpeg_path: &str, audio_data: &[u8]) -> Result<Vec<i16>, String> {
+    let mut command = std::process::Command::new(ffmpeg_path);
+    command
+        .arg("-i")
+        .arg("pipe:0") // Read from stdin
+        .arg("-ar")
+        .arg(TARGET_SAMPLE_RATE.to_string())
+        .arg("-ac")
+        .arg("1") // Mono
+        .arg("-f")
+        .arg("s16le") // Raw PCM
+        .arg("-"); // Pipe to stdout
+
+    let output = crate::pipe::output_with_stdin(&mut command, audio_data.to_vec())
+        .map_err(|e| format!("Fehler beim Ausführen von ffmpeg: {}", e))?;
+
+    if !output.status.success() {
+        let err_msg = String::from_utf8_lossy(&output.stderr);
+        return Err(format!("FFmpeg Konvertierungsfehler: {}", err_msg));
+    }
+
+    Ok(output
+        .stdout
+        .chunks_exact(2)
+        .map(|chunk| i16::from_le_bytes([chunk[0], chunk[1]]))
+        .collect())
+}
+
+/// Average interleaved channels into mono
+fn downmix(interleaved: &[f32], channels: usize, output: &mut Vec<f32>) {
+    if channels <= 1 {
+        output.extend_from_slice(interleaved);
+        return;
+    }
+    output.extend(
+        interleaved
+            .chunks_exact(channels)
+            .map(|frame| frame.iter().sum::<f32>() / channels as f32),
+    );
+}
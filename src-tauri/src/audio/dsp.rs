@@ -0,0 +1,453 @@
+//! Audio Preprocessing (DSP Chain)
+//!
+//! Cleans up recordings before the Silence Guard and STT:
+//! high-pass (removes DC offset and rumble) → spectral noise suppression → normalization.
+//! Every stage is optional and runs offline on the complete 16 kHz buffer.
+
+use std::f32::consts::PI;
+use std::path::{Path, PathBuf};
+
+use realfft::num_complex::Complex;
+use realfft::RealFftPlanner;
+use serde::{Deserialize, Serialize};
+
+use super::AudioBuffer;
+
+/// STFT frame for noise suppression (32 ms at 16 kHz)
+const FFT_LEN: usize = 512;
+const HOP_LEN: usize = FFT_LEN / 2;
+
+/// Share of the quietest frames used as noise profile
+const NOISE_PROFILE_SHARE: f32 = 0.15;
+
+/// Over-subtraction factor: removes a bit more than the estimated noise against residual hiss
+const OVER_SUBTRACTION: f32 = 2.0;
+
+/// Gain smoothing over time (0..1), suppresses "musical noise" artifacts
+const GAIN_SMOOTHING: f32 = 0.5;
+
+/// BS.1770 gating block and step
+const LOUDNESS_BLOCK_MS: u32 = 400;
+const LOUDNESS_STEP_MS: u32 = 100;
+const ABSOLUTE_GATE_LUFS: f32 = -70.0;
+const RELATIVE_GATE_LU: f32 = -10.0;
+
+/// Level normalization before STT
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum NormalizeMode {
+    #[default]
+    Off,
+    /// Scale so the loudest sample hits `target_peak_dbfs`
+    Peak,
+    /// Integrated loudness (EBU R128 / BS.1770) to `target_lufs`, peaks capped at `target_peak_dbfs`
+    Loudness,
+}
+
+/// DSP chain settings (persisted in the backend config)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct DspConfig {
+    pub high_pass: bool,
+    /// High-pass corner frequency (voice starts at ~80 Hz)
+    pub high_pass_hz: f32,
+    pub noise_suppression: bool,
+    /// Maximum attenuation of noise-only frequency bins
+    pub suppression_db: f32,
+    pub normalize: NormalizeMode,
+    pub target_peak_dbfs: f32,
+    pub target_lufs: f32,
+    /// Upper bound for amplification, so near-silent recordings don't turn into loud noise
+    pub max_gain_db: f32,
+    /// Keep original and processed WAV for comparison (not in strict RAM mode)
+    pub save_processed: bool,
+}
+
+impl Default for DspConfig {
+    fn default() -> Self {
+        Self {
+            high_pass: false,
+            high_pass_hz: 80.0,
+            noise_suppression: false,
+            suppression_db: 18.0,
+            normalize: NormalizeMode::Off,
+            target_peak_dbfs: -1.0,
+            target_lufs: -20.0,
+            max_gain_db: 20.0,
+            save_processed: false,
+        }
+    }
+}
+
+impl DspConfig {
+    /// Whether any stage changes the audio
+    pub fn is_active(&self) -> bool {
+        self.high_pass || self.noise_suppression || self.normalize != NormalizeMode::Off
+    }
+}
+
+/// Run all enabled stages and return the processed buffer
+pub fn process(buffer: &AudioBuffer, config: &DspConfig) -> AudioBuffer {
+    let mut samples: Vec<f32> = buffer
+        .samples
+        .iter()
+        .map(|&s| s as f32 / i16::MAX as f32)
+        .collect();
+    let sample_rate = buffer.sample_rate as f32;
+
+    if config.high_pass {
+        Biquad::high_pass(config.high_pass_hz, sample_rate).run(&mut samples);
+    }
+
+    if config.noise_suppression {
+        samples = suppress_noise(&samples, config.suppression_db);
+    }
+
+    let gain_db = match config.normalize {
+        NormalizeMode::Off => None,
+        NormalizeMode::Peak => peak_dbfs(&samples).map(|peak| config.target_peak_dbfs - peak),
+        NormalizeMode::Loudness => integrated_loudness(&samples, buffer.sample_rate).map(|lufs| {
+            // Never push peaks above the target, there is no limiter
+            let headroom = peak_dbfs(&samples)
+                .map(|peak| config.target_peak_dbfs - peak)
+                .unwrap_or(0.0);
+            (config.target_lufs - lufs).min(headroom)
+        }),
+    };
+    if let Some(gain_db) = gain_db {
+        let gain = db_to_amplitude(gain_db.min(config.max_gain_db));
+        samples.iter_mut().for_each(|s| *s *= gain);
+    }
+
+    AudioBuffer {
+        samples: samples.into_iter().map(super::f32_to_i16).collect(),
+        sample_rate: buffer.sample_rate,
+    }
+}
+
+/// Write original and processed audio side by side as `<stamp>-original.wav` / `<stamp>-processed.wav`
+pub fn save_comparison(
+    dir: &Path,
+    original: &AudioBuffer,
+    processed: &AudioBuffer,
+) -> Result<PathBuf, String> {
+    std::fs::create_dir_all(dir)
+        .map_err(|e| format!("Fehler beim Erstellen von {}: {}", dir.display(), e))?;
+
+    let stamp = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_millis())
+        .unwrap_or_default();
+    for (suffix, buffer) in [("original", original), ("processed", processed)] {
+        let path = dir.join(format!("{}-{}.wav", stamp, suffix));
+        std::fs::write(&path, super::wav::encode(buffer))
+            .map_err(|e| format!("Fehler beim Speichern von {}: {}", path.display(), e))?;
+    }
+    Ok(dir.join(format!("{}-processed.wav", stamp)))
+}
+
+/// Second-order IIR section (RBJ cookbook / BS.1770 coefficients)
+struct Biquad {
+    b: [f32; 3],
+    a: [f32; 2],
+}
+
+impl Biquad {
+    /// Butterworth high-pass
+    fn high_pass(cutoff_hz: f32, sample_rate: f32) -> Self {
+        let w0 = 2.0 * PI * cutoff_hz / sample_rate;
+        let alpha = w0.sin() / (2.0 * std::f32::consts::FRAC_1_SQRT_2);
+        let cos = w0.cos();
+        let a0 = 1.0 + alpha;
+        Self {
+            b: [
+                (1.0 + cos) / 2.0 / a0,
+                -(1.0 + cos) / a0,
+                (1.0 + cos) / 2.0 / a0,
+            ],
+            a: [-2.0 * cos / a0, (1.0 - alpha) / a0],
+        }
+    }
+
+    /// K-weighting stage 1: high shelf modelling the head
+    fn k_shelf(sample_rate: f32) -> Self {
+        let (gain_db, f0, q) = (3.999_843_9_f32, 1_681.974_5_f32, 0.707_175_2_f32);
+        let k = (PI * f0 / sample_rate).tan();
+        let vh = 10f32.powf(gain_db / 20.0);
+        let vb = vh.powf(0.499_666_8);
+        let a0 = 1.0 + k / q + k * k;
+        Self {
+            b: [
+                (vh + vb * k / q + k * k) / a0,
+                2.0 * (k * k - vh) / a0,
+                (vh - vb * k / q + k * k) / a0,
+            ],
+            a: [2.0 * (k * k - 1.0) / a0, (1.0 - k / q + k * k) / a0],
+        }
+    }
+
+    /// K-weighting stage 2: RLB high-pass
+    fn k_high_pass(sample_rate: f32) -> Self {
+        let (f0, q) = (38.135_47_f32, 0.500_327_f32);
+        let k = (PI * f0 / sample_rate).tan();
+        let a0 = 1.0 + k / q + k * k;
+        Self {
+            b: [1.0, -2.0, 1.0],
+            a: [2.0 * (k * k - 1.0) / a0, (1.0 - k / q + k * k) / a0],
+        }
+    }
+
+    /// Filter in place (transposed direct form II)
+    fn run(&self, samples: &mut [f32]) {
+        let (mut z1, mut z2) = (0.0f32, 0.0f32);
+        for sample in samples.iter_mut() {
+            let x = *sample;
+            let y = self.b[0] * x + z1;
+            z1 = self.b[1] * x - self.a[0] * y + z2;
+            z2 = self.b[2] * x - self.a[1] * y;
+            *sample = y;
+        }
+    }
+}
+
+/// Spectral subtraction against a stationary noise profile taken from the quietest frames
+fn suppress_noise(samples: &[f32], suppression_db: f32) -> Vec<f32> {
+    if samples.len() < FFT_LEN {
+        return samples.to_vec();
+    }
+
+    // sqrt-Hann for analysis and synthesis: at 50% overlap the product sums to 1
+    let window: Vec<f32> = (0..FFT_LEN)
+        .map(|i| (0.5 - 0.5 * (2.0 * PI * i as f32 / FFT_LEN as f32).cos()).sqrt())
+        .collect();
+
+    // Pad so the first and last samples are covered by two frames
+    let mut padded = vec![0.0f32; HOP_LEN];
+    padded.extend_from_slice(samples);
+    padded.resize(padded.len() + FFT_LEN, 0.0);
+    let frame_count = (padded.len() - FFT_LEN) / HOP_LEN + 1;
+
+    let mut planner = RealFftPlanner::<f32>::new();
+    let forward = planner.plan_fft_forward(FFT_LEN);
+    let inverse = planner.plan_fft_inverse(FFT_LEN);
+
+    let mut spectra: Vec<Vec<Complex<f32>>> = Vec::with_capacity(frame_count);
+    let mut frame = forward.make_input_vec();
+    for index in 0..frame_count {
+        let start = index * HOP_LEN;
+        for (i, value) in frame.iter_mut().enumerate() {
+            *value = padded[start + i] * window[i];
+        }
+        let mut spectrum = forward.make_output_vec();
+        if forward.process(&mut frame, &mut spectrum).is_err() {
+            return samples.to_vec();
+        }
+        spectra.push(spectrum);
+    }
+
+    // Noise profile: mean power spectrum of the quietest frames. Only frames fully inside
+    // the signal count, the zero-padded edge frames would pull the profile towards silence.
+    let interior = 1..=(samples.len() - HOP_LEN) / HOP_LEN;
+    let mut by_energy: Vec<(usize, f32)> = interior
+        .map(|i| (i, spectra[i].iter().map(|c| c.norm_sqr()).sum()))
+        .collect();
+    by_energy.sort_by(|a, b| a.1.total_cmp(&b.1));
+    let profile_len = ((by_energy.len() as f32 * NOISE_PROFILE_SHARE) as usize).max(1);
+    let bins = FFT_LEN / 2 + 1;
+    let mut noise = vec![0.0f32; bins];
+    for &(index, _) in &by_energy[..profile_len] {
+        for (bin, c) in spectra[index].iter().enumerate() {
+            noise[bin] += c.norm_sqr() / profile_len as f32;
+        }
+    }
+
+    let floor = db_to_amplitude(-suppression_db);
+    let mut gains = vec![1.0f32; bins];
+    let mut output = vec![0.0f32; padded.len()];
+    for (index, spectrum) in spectra.iter_mut().enumerate() {
+        for (bin, c) in spectrum.iter_mut().enumerate() {
+            let power = c.norm_sqr().max(f32::MIN_POSITIVE);
+            let gain = (1.0 - OVER_SUBTRACTION * noise[bin] / power)
+                .max(0.0)
+                .sqrt()
+                .max(floor);
+            gains[bin] = GAIN_SMOOTHING * gains[bin] + (1.0 - GAIN_SMOOTHING) * gain;
+            *c *= gains[bin];
+        }
+        // The inverse real FFT expects purely real DC and Nyquist bins
+        spectrum[0].im = 0.0;
+        spectrum[bins - 1].im = 0.0;
+
+        if inverse.process(spectrum, &mut frame).is_err() {
+            return samples.to_vec();
+        }
+        let start = index * HOP_LEN;
+        for (i, value) in frame.iter().enumerate() {
+            output[start + i] += value * window[i] / FFT_LEN as f32;
+        }
+    }
+
+    output[HOP_LEN..HOP_LEN + samples.len()].to_vec()
+}
+
+/// Integrated loudness in LUFS (BS.1770 with absolute and relative gate), `None` for silence
+fn integrated_loudness(samples: &[f32], sample_rate: u32) -> Option<f32> {
+    let mut weighted = samples.to_vec();
+    Biquad::k_shelf(sample_rate as f32).run(&mut weighted);
+    Biquad::k_high_pass(sample_rate as f32).run(&mut weighted);
+
+    let block = (sample_rate * LOUDNESS_BLOCK_MS / 1000) as usize;
+    let step = (sample_rate * LOUDNESS_STEP_MS / 1000) as usize;
+    let powers: Vec<f32> = if weighted.len() < block {
+        // Shorter than one block: measure the whole clip
+        vec![mean_square(&weighted)]
+    } else {
+        (0..=(weighted.len() - block) / step)
+            .map(|i| mean_square(&weighted[i * step..i * step + block]))
+            .collect()
+    };
+
+    let gated_mean = |threshold: f32| {
+        let gated: Vec<f32> = powers
+            .iter()
+            .copied()
+            .filter(|&p| power_to_lufs(p) > threshold)
+            .collect();
+        (!gated.is_empty()).then(|| gated.iter().sum::<f32>() / gated.len() as f32)
+    };
+
+    let ungated = gated_mean(ABSOLUTE_GATE_LUFS)?;
+    let relative_gate = power_to_lufs(ungated) + RELATIVE_GATE_LU;
+    gated_mean(relative_gate.max(ABSOLUTE_GATE_LUFS)).map(power_to_lufs)
+}
+
+fn mean_square(samples: &[f32]) -> f32 {
+    if samples.is_empty() {
+        return 0.0;
+    }
+    samples.iter().map(|s| s * s).sum::<f32>() / samples.len() as f32
+}
+
+fn power_to_lufs(power: f32) -> f32 {
+    -0.691 + 10.0 * power.max(f32::MIN_POSITIVE).log10()
+}
+
+/// Loudest sample in dBFS, `None` for digital silence
+fn peak_dbfs(samples: &[f32]) -> Option<f32> {
+    let peak = samples.iter().fold(0.0f32, |max, s| max.max(s.abs()));
+    (peak > 0.0).then(|| 20.0 * peak.log10())
+}
+
+fn db_to_amplitude(db: f32) -> f32 {
+    10f32.powf(db / 20.0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const RATE: u32 = 16_000;
+
+    fn sine(freq: f32, amplitude: f32, len: usize) -> Vec<f32> {
+        (0..len)
+            .map(|i| amplitude * (2.0 * PI * freq * i as f32 / RATE as f32).sin())
+            .collect()
+    }
+
+    /// Deterministic white noise in -amplitude..amplitude
+    fn noise(amplitude: f32, len: usize) -> Vec<f32> {
+        let mut state = 0x2545_f491_u32;
+        (0..len)
+            .map(|_| {
+                state = state.wrapping_mul(1_664_525).wrapping_add(1_013_904_223);
+                amplitude * ((state >> 8) as f32 / (1 << 23) as f32 - 1.0)
+            })
+            .collect()
+    }
+
+    fn buffer(samples: &[f32]) -> AudioBuffer {
+        AudioBuffer::new(
+            samples
+                .iter()
+                .copied()
+                .map(crate::audio::f32_to_i16)
+                .collect(),
+        )
+    }
+
+    fn to_f32(buffer: &AudioBuffer) -> Vec<f32> {
+        buffer
+            .samples
+            .iter()
+            .map(|&s| s as f32 / i16::MAX as f32)
+            .collect()
+    }
+
+    fn rms(samples: &[f32]) -> f32 {
+        mean_square(samples).sqrt()
+    }
+
+    #[test]
+    fn high_pass_removes_dc_offset() {
+        let input: Vec<f32> = sine(440.0, 0.3, RATE as usize * 2)
+            .into_iter()
+            .map(|s| s + 0.2)
+            .collect();
+        let config = DspConfig {
+            high_pass: true,
+            ..DspConfig::default()
+        };
+        let output = to_f32(&process(&buffer(&input), &config));
+
+        // After the filter settles the offset is gone, the voice band is untouched
+        let settled = &output[RATE as usize..];
+        let mean = settled.iter().sum::<f32>() / settled.len() as f32;
+        assert!(mean.abs() < 0.002, "DC left: {}", mean);
+        assert!((rms(settled) - 0.3 / 2f32.sqrt()).abs() < 0.01);
+    }
+
+    #[test]
+    fn loudness_normalization_hits_target() {
+        let input = sine(1_000.0, 0.05, RATE as usize * 3);
+        let config = DspConfig {
+            normalize: NormalizeMode::Loudness,
+            target_lufs: -20.0,
+            ..DspConfig::default()
+        };
+        let before = integrated_loudness(&input, RATE).unwrap();
+        let output = to_f32(&process(&buffer(&input), &config));
+        let after = integrated_loudness(&output, RATE).unwrap();
+
+        assert!(before < -28.0, "input already at {} LUFS", before);
+        assert!((after + 20.0).abs() < 0.5, "normalized to {} LUFS", after);
+    }
+
+    #[test]
+    fn every_stage_keeps_the_length() {
+        let config = DspConfig {
+            high_pass: true,
+            noise_suppression: true,
+            normalize: NormalizeMode::Loudness,
+            ..DspConfig::default()
+        };
+        for len in [0, 100, FFT_LEN, RATE as usize + 123] {
+            let input = noise(0.1, len);
+            assert_eq!(process(&buffer(&input), &config).samples.len(), len);
+        }
+    }
+
+    #[test]
+    fn noise_profile_ignores_padded_edges() {
+        // Short noise-only clip: the padded edge frames are the quietest ones by far,
+        // a profile built from them would leave the noise untouched
+        let input = noise(0.1, 2_400);
+        let output = suppress_noise(&input, 18.0);
+        assert_eq!(output.len(), input.len());
+        assert!(
+            rms(&output) < rms(&input) / 2.0,
+            "noise {} -> {}",
+            rms(&input),
+            rms(&output)
+        );
+    }
+}
@@ -0,0 +1,176 @@
+//! End-of-Speech Detection
+//!
+//! Watches the live level readings of a recording and decides when it should stop on its
+//! own: after a silence gap following speech (optional) or when the duration cap is hit.
+
+use serde::{Deserialize, Serialize};
+
+use super::level::{AudioLevel, WINDOW_MS};
+
+/// Speech needed before trailing silence may end the recording (ignores lone clicks)
+const MIN_SPEECH_MS: u32 = 250;
+
+/// Endpointing settings (persisted in the backend config)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct EndpointConfig {
+    /// Stop automatically after trailing silence
+    pub auto_stop: bool,
+    /// Silence after speech that ends the recording
+    pub silence_ms: u32,
+    /// Hard cap for a single recording, applies with or without auto-stop. 0 = unlimited
+    /// (default), so long meeting recordings are never cut off.
+    pub max_duration_ms: u32,
+}
+
+impl Default for EndpointConfig {
+    fn default() -> Self {
+        Self {
+            auto_stop: false,
+            silence_ms: 1200,
+            max_duration_ms: 0,
+        }
+    }
+}
+
+/// Why a recording was ended automatically
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EndReason {
+    Silence,
+    MaxDuration,
+}
+
+/// Streaming endpointer, fed with one `AudioLevel` per window
+pub struct Endpointer {
+    config: EndpointConfig,
+    elapsed_ms: u32,
+    speech_ms: u32,
+    silence_ms: u32,
+    ended: bool,
+}
+
+impl Endpointer {
+    pub fn new(config: EndpointConfig) -> Self {
+        Self {
+            config,
+            elapsed_ms: 0,
+            speech_ms: 0,
+            silence_ms: 0,
+            ended: false,
+        }
+    }
+
+    /// Start over, e.g. for the next utterance in continuous dictation
+    pub fn reset(&mut self) {
+        *self = Self::new(self.config.clone());
+    }
+
+    /// Returns the reason once, as soon as the recording should end
+    pub fn push(&mut self, level: &AudioLevel) -> Option<EndReason> {
+        if self.ended {
+            return None;
+        }
+
+        self.elapsed_ms += WINDOW_MS;
+        if level.is_speech {
+            self.speech_ms += WINDOW_MS;
+            self.silence_ms = 0;
+        } else {
+            self.silence_ms += WINDOW_MS;
+        }
+
+        let reason =
+            if self.config.max_duration_ms > 0 && self.elapsed_ms >= self.config.max_duration_ms {
+                Some(EndReason::MaxDuration)
+            } else if self.config.auto_stop
+                && self.speech_ms >= MIN_SPEECH_MS
+                && self.silence_ms >= self.config.silence_ms
+            {
+                Some(EndReason::Silence)
+            } else {
+                None
+            };
+
+        self.ended = reason.is_some();
+        reason
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn level(is_speech: bool) -> AudioLevel {
+        AudioLevel {
+            rms: 0.0,
+            peak: 0.0,
+            is_speech,
+        }
+    }
+
+    /// Feed `ms` of speech or silence, returning the first end reason and when it fired
+    fn feed(endpointer: &mut Endpointer, is_speech: bool, ms: u32) -> Option<(EndReason, u32)> {
+        (1..=ms / WINDOW_MS).find_map(|i| {
+            endpointer
+                .push(&level(is_speech))
+                .map(|reason| (reason, i * WINDOW_MS))
+        })
+    }
+
+    #[test]
+    fn stops_after_trailing_silence() {
+        let mut endpointer = Endpointer::new(EndpointConfig {
+            auto_stop: true,
+            ..EndpointConfig::default()
+        });
+
+        assert_eq!(feed(&mut endpointer, true, 2_000), None);
+        // A pause shorter than `silence_ms` keeps recording
+        assert_eq!(feed(&mut endpointer, false, 800), None);
+        assert_eq!(feed(&mut endpointer, true, 500), None);
+        assert_eq!(
+            feed(&mut endpointer, false, 5_000),
+            Some((EndReason::Silence, 1_200))
+        );
+        // Reported once
+        assert_eq!(feed(&mut endpointer, false, 5_000), None);
+    }
+
+    #[test]
+    fn silence_before_speech_does_not_stop() {
+        let mut endpointer = Endpointer::new(EndpointConfig {
+            auto_stop: true,
+            ..EndpointConfig::default()
+        });
+        assert_eq!(feed(&mut endpointer, false, 10_000), None);
+        // A click below MIN_SPEECH_MS does not arm the silence stop either
+        assert_eq!(feed(&mut endpointer, true, 100), None);
+        assert_eq!(feed(&mut endpointer, false, 10_000), None);
+    }
+
+    #[test]
+    fn duration_cap_applies_with_and_without_auto_stop() {
+        for auto_stop in [true, false] {
+            let mut endpointer = Endpointer::new(EndpointConfig {
+                auto_stop,
+                max_duration_ms: 3_000,
+                ..EndpointConfig::default()
+            });
+            assert_eq!(
+                feed(&mut endpointer, true, 10_000),
+                Some((EndReason::MaxDuration, 3_000))
+            );
+        }
+    }
+
+    #[test]
+    fn defaults_never_end_a_long_recording() {
+        let mut endpointer = Endpointer::new(EndpointConfig::default());
+        // 40 minute meeting with long pauses
+        for _ in 0..20 {
+            assert_eq!(feed(&mut endpointer, true, 60_000), None);
+            assert_eq!(feed(&mut endpointer, false, 60_000), None);
+        }
+    }
+}
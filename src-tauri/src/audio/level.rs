@@ -0,0 +1,84 @@
+//! Live Level Meter
+//!
+//! Summarizes captured PCM into periodic level readings (RMS, peak, speech flag) for the
+//! HUD visualizer and the tray. Readings are produced per window of audio, not per
+//! callback, which keeps the event rate fixed regardless of the device buffer size.
+
+use serde::Serialize;
+
+use super::vad::{FrameClassifier, VadConfig};
+use super::TARGET_SAMPLE_RATE;
+
+/// Length of one reading (20 events per second)
+pub const WINDOW_MS: u32 = 50;
+
+/// One level reading, values normalized to 0..1 of full scale
+#[derive(Debug, Clone, Copy, Serialize)]
+pub struct AudioLevel {
+    pub rms: f32,
+    pub peak: f32,
+    /// Whether the VAD classified any frame in this window as speech
+    pub is_speech: bool,
+}
+
+/// Accumulates 16 kHz samples and yields one `AudioLevel` per window
+pub struct LevelMeter {
+    classifier: FrameClassifier,
+    frame_len: usize,
+    window_len: usize,
+    /// Samples of the VAD frame currently being filled
+    frame: Vec<i16>,
+    sum_squares: f64,
+    peak: i16,
+    count: usize,
+    is_speech: bool,
+}
+
+impl LevelMeter {
+    pub fn new(vad_config: VadConfig) -> Self {
+        let classifier = FrameClassifier::new(vad_config);
+        let frame_len = classifier.frame_len(TARGET_SAMPLE_RATE).max(1);
+        Self {
+            classifier,
+            frame_len,
+            window_len: (TARGET_SAMPLE_RATE * WINDOW_MS / 1000) as usize,
+            frame: Vec::with_capacity(frame_len),
+            sum_squares: 0.0,
+            peak: 0,
+            count: 0,
+            is_speech: false,
+        }
+    }
+
+    /// Feed samples and call `emit` for every completed window
+    pub fn push(&mut self, samples: &[i16], mut emit: impl FnMut(AudioLevel)) {
+        for &sample in samples {
+            self.sum_squares += sample as f64 * sample as f64;
+            self.peak = self.peak.max(sample.saturating_abs());
+            self.count += 1;
+
+            self.frame.push(sample);
+            if self.frame.len() == self.frame_len {
+                self.is_speech |= self.classifier.classify(&self.frame);
+                self.frame.clear();
+            }
+
+            if self.count == self.window_len {
+                emit(self.take_level());
+            }
+        }
+    }
+
+    fn take_level(&mut self) -> AudioLevel {
+        let level = AudioLevel {
+            rms: ((self.sum_squares / self.count as f64).sqrt() / i16::MAX as f64) as f32,
+            peak: self.peak as f32 / i16::MAX as f32,
+            is_speech: self.is_speech,
+        };
+        self.sum_squares = 0.0;
+        self.peak = 0;
+        self.count = 0;
+        self.is_speech = false;
+        level
+    }
+}
@@ -0,0 +1,117 @@
+//! Audio Processing Module
+//!
+//! Turns recorded audio into 16 kHz mono PCM for the Silence Guard and local Whisper.
+
+pub mod capture;
+pub mod chunk;
+pub mod decoder;
+pub mod dsp;
+pub mod endpoint;
+pub mod level;
+pub mod opus;
+pub mod resample;
+pub mod vad;
+pub mod wav;
+
+use std::ops::Range;
+
+use serde::{Deserialize, Serialize};
+
+/// Sample rate expected by Whisper (and used by the Silence Guard)
+pub const TARGET_SAMPLE_RATE: u32 = 16_000;
+
+/// Convert a float sample in [-1.0, 1.0] to PCM16
+pub fn f32_to_i16(sample: f32) -> i16 {
+    (sample.clamp(-1.0, 1.0) * i16::MAX as f32) as i16
+}
+
+/// Silence trimming before STT (persisted in the backend config)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TrimConfig {
+    pub enabled: bool,
+    /// Audio kept before the first and after the last speech segment
+    pub padding_ms: u32,
+}
+
+impl Default for TrimConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            padding_ms: 300,
+        }
+    }
+}
+
+/// Encoded audio ready for upload to a transcription API
+pub struct EncodedAudio {
+    pub data: Vec<u8>,
+    pub file_name: &'static str,
+    pub mime_type: &'static str,
+}
+
+impl EncodedAudio {
+    /// Recording as received from the frontend (MediaRecorder)
+    pub fn webm(data: Vec<u8>) -> Self {
+        Self {
+            data,
+            file_name: "audio.webm",
+            mime_type: "audio/webm",
+        }
+    }
+}
+
+/// Decoded mono PCM, produced once per request and shared by all pipeline stages
+#[derive(Debug, Clone)]
+pub struct AudioBuffer {
+    pub samples: Vec<i16>,
+    pub sample_rate: u32,
+}
+
+impl AudioBuffer {
+    /// Wrap 16 kHz mono PCM samples
+    pub fn new(samples: Vec<i16>) -> Self {
+        Self {
+            samples,
+            sample_rate: TARGET_SAMPLE_RATE,
+        }
+    }
+
+    /// Decode a recording to 16 kHz mono PCM.
+    /// Uses the native decoder unless an ffmpeg binary is explicitly configured as override.
+    pub fn decode(audio_data: &[u8], ffmpeg_path: &str) -> Result<Self, String> {
+        let samples = if ffmpeg_path.is_empty() {
+            decoder::decode(audio_data)?
+        } else {
+            decoder::decode_with_ffmpeg(ffmpeg_path, audio_data)?
+        };
+        Ok(Self::new(samples))
+    }
+
+    /// Decode an imported audio or video file (audio track) to 16 kHz mono PCM
+    pub fn decode_file(path: &std::path::Path) -> Result<Self, String> {
+        decoder::decode_file(path).map(Self::new)
+    }
+
+    /// Length of the audio in milliseconds
+    pub fn duration_ms(&self) -> u64 {
+        self.samples.len() as u64 * 1000 / self.sample_rate as u64
+    }
+
+    /// Copy of the given sample range
+    pub fn slice(&self, range: Range<usize>) -> Self {
+        Self {
+            samples: self.samples[range].to_vec(),
+            sample_rate: self.sample_rate,
+        }
+    }
+
+    /// Encode as Ogg/Opus for upload
+    pub fn encode_opus(&self) -> Result<EncodedAudio, String> {
+        Ok(EncodedAudio {
+            data: opus::encode_ogg(self)?,
+            file_name: "audio.ogg",
+            mime_type: "audio/ogg",
+        })
+    }
+}
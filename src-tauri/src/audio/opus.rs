@@ -0,0 +1,187 @@
+//! Ogg/Opus Encoder
+//!
+//! Re-encodes PCM for cloud upload. Opus keeps trimmed uploads several times smaller than WAV.
+
+use audiopus::{coder::Encoder, Application, Bitrate, Channels, SampleRate};
+use ogg::writing::{PacketWriteEndInfo, PacketWriter};
+
+use super::AudioBuffer;
+
+/// 20 ms frames at 16 kHz
+const FRAME_SAMPLES: usize = 320;
+
+/// Ogg Opus granule positions are always counted at 48 kHz
+const GRANULE_RATE: u64 = 48_000;
+
+/// Plenty for speech at 16 kHz
+const BITRATE: i32 = 24_000;
+
+/// Upper bound for a single encoded Opus packet
+const MAX_PACKET: usize = 4000;
+
+const STREAM_SERIAL: u32 = 1;
+
+/// Encode a 16 kHz mono buffer as an Ogg/Opus file
+pub fn encode_ogg(buffer: &AudioBuffer) -> Result<Vec<u8>, String> {
+    let sample_rate = match buffer.sample_rate {
+        16_000 => SampleRate::Hz16000,
+        48_000 => SampleRate::Hz48000,
+        rate => return Err(format!("Abtastrate {} Hz wird nicht unterstützt", rate)),
+    };
+    let mut encoder = Encoder::new(sample_rate, Channels::Mono, Application::Voip)
+        .map_err(|e| format!("Opus-Encoder Initialisierung fehlgeschlagen: {}", e))?;
+    encoder
+        .set_bitrate(Bitrate::BitsPerSecond(BITRATE))
+        .map_err(|e| format!("Opus-Bitrate konnte nicht gesetzt werden: {}", e))?;
+
+    let granule_scale = GRANULE_RATE / buffer.sample_rate as u64;
+    let frame_samples = FRAME_SAMPLES * buffer.sample_rate as usize / 16_000;
+    let lookahead = encoder.lookahead().unwrap_or(0) as usize;
+    let pre_skip = lookahead as u64 * granule_scale;
+
+    let mut writer = PacketWriter::new(Vec::new());
+    let write_err = |e: std::io::Error| format!("Fehler beim Schreiben des Ogg-Streams: {}", e);
+
+    writer
+        .write_packet(
+            opus_head(pre_skip as u16, buffer.sample_rate),
+            STREAM_SERIAL,
+            PacketWriteEndInfo::EndPage,
+            0,
+        )
+        .map_err(write_err)?;
+    writer
+        .write_packet(opus_tags(), STREAM_SERIAL, PacketWriteEndInfo::EndPage, 0)
+        .map_err(write_err)?;
+
+    // Encode past the end so the encoder lookahead is flushed as well
+    let total_samples = buffer.samples.len();
+    let frame_count = (total_samples + lookahead).div_ceil(frame_samples).max(1);
+    let mut packet = vec![0u8; MAX_PACKET];
+
+    for index in 0..frame_count {
+        let start = (index * frame_samples).min(total_samples);
+        let end = (start + frame_samples).min(total_samples);
+
+        // Pad the tail with silence
+        let mut frame = buffer.samples[start..end].to_vec();
+        frame.resize(frame_samples, 0);
+
+        let len = encoder
+            .encode(&frame, &mut packet)
+            .map_err(|e| format!("Opus-Kodierung fehlgeschlagen: {}", e))?;
+
+        let is_last = index + 1 == frame_count;
+        // The final granule position marks the real end, so the padding is dropped on decode
+        let granule = if is_last {
+            pre_skip + total_samples as u64 * granule_scale
+        } else {
+            pre_skip + ((index + 1) * frame_samples) as u64 * granule_scale
+        };
+        let end_info = if is_last {
+            PacketWriteEndInfo::EndStream
+        } else {
+            PacketWriteEndInfo::NormalPacket
+        };
+
+        writer
+            .write_packet(
+                packet[..len].to_vec().into_boxed_slice(),
+                STREAM_SERIAL,
+                end_info,
+                granule,
+            )
+            .map_err(write_err)?;
+    }
+
+    Ok(writer.into_inner())
+}
+
+/// Identification header (RFC 7845, section 5.1)
+fn opus_head(pre_skip: u16, input_rate: u32) -> Box<[u8]> {
+    let mut head = Vec::with_capacity(19);
+    head.extend_from_slice(b"OpusHead");
+    head.push(1); // Version
+    head.push(1); // Mono
+    head.extend_from_slice(&pre_skip.to_le_bytes());
+    head.extend_from_slice(&input_rate.to_le_bytes());
+    head.extend_from_slice(&0i16.to_le_bytes()); // Output gain
+    head.push(0); // Channel mapping family
+    head.into_boxed_slice()
+}
+
+/// Comment header (RFC 7845, section 5.2)
+fn opus_tags() -> Box<[u8]> {
+    let vendor = b"voice-intelligence";
+    let mut tags = Vec::with_capacity(16 + vendor.len());
+    tags.extend_from_slice(b"OpusTags");
+    tags.extend_from_slice(&(vendor.len() as u32).to_le_bytes());
+    tags.extend_from_slice(vendor);
+    tags.extend_from_slice(&0u32.to_le_bytes()); // No user comments
+    tags.into_boxed_slice()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sine(freq: f32, seconds: f32, sample_rate: u32) -> AudioBuffer {
+        let len = (seconds * sample_rate as f32) as usize;
+        AudioBuffer {
+            samples: (0..len)
+                .map(|i| {
+                    let t = i as f32 / sample_rate as f32;
+                    (8_000.0 * (2.0 * std::f32::consts::PI * freq * t).sin()) as i16
+                })
+                .collect(),
+            sample_rate,
+        }
+    }
+
+    /// Sign changes per second, twice the frequency of a sine
+    fn crossings_per_second(samples: &[i16], sample_rate: u32) -> f32 {
+        let crossings = samples
+            .windows(2)
+            .filter(|pair| (pair[0] < 0) != (pair[1] < 0))
+            .count();
+        crossings as f32 * sample_rate as f32 / samples.len() as f32
+    }
+
+    #[test]
+    fn round_trip_keeps_rate_and_duration() {
+        for rate in [16_000, 48_000] {
+            let original = sine(440.0, 1.5, rate);
+            let ogg = encode_ogg(&original).unwrap();
+            assert!(
+                ogg.len() < original.samples.len() / 2,
+                "{} bytes",
+                ogg.len()
+            );
+
+            // Decoded at 16 kHz with the pre-skip removed. The decoder does not apply the
+            // end granule (ffmpeg does), so the padded last frame and the lookahead remain.
+            let decoded = crate::audio::decoder::decode(&ogg).unwrap();
+            let expected = original.duration_ms() as usize * 16;
+            let padding = FRAME_SAMPLES + 16 * 7;
+            assert!(
+                (expected..expected + padding).contains(&decoded.len()),
+                "{} Hz: {} samples, expected {}",
+                rate,
+                decoded.len(),
+                expected
+            );
+            let steady = &decoded[1_600..decoded.len() - 1_600];
+            let crossings = crossings_per_second(steady, 16_000);
+            assert!(
+                (crossings - 880.0).abs() < 10.0,
+                "{} crossings/s",
+                crossings
+            );
+        }
+    }
+
+    #[test]
+    fn rejects_unsupported_rates() {
+        assert!(encode_ogg(&sine(440.0, 0.1, 44_100)).is_err());
+    }
+}
@@ -0,0 +1,176 @@
+//! Streaming Resampler
+//!
+//! Converts mono audio of arbitrary length to the target rate in fixed-size chunks,
+//! so it works for whole decoded files as well as live microphone input.
+
+use rubato::{FftFixedIn, Resampler};
+
+/// Input chunk size for the resampler
+const CHUNK: usize = 1024;
+
+/// Mono resampler that accepts input in arbitrarily sized pieces
+pub struct StreamResampler {
+    inner: Option<FftFixedIn<f32>>,
+    ratio: f64,
+    pending: Vec<f32>,
+    /// Filter delay still to be dropped from the start of the output
+    delay: usize,
+    input_len: usize,
+    output_len: usize,
+}
+
+impl StreamResampler {
+    pub fn new(from: u32, to: u32) -> Result<Self, String> {
+        let inner = if from == to {
+            None
+        } else {
+            Some(
+                FftFixedIn::<f32>::new(from as usize, to as usize, CHUNK, 2, 1)
+                    .map_err(|e| format!("Resampler Initialisierung fehlgeschlagen: {}", e))?,
+            )
+        };
+        let delay = inner.as_ref().map(|r| r.output_delay()).unwrap_or(0);
+
+        Ok(Self {
+            inner,
+            ratio: to as f64 / from as f64,
+            pending: Vec::with_capacity(CHUNK),
+            delay,
+            input_len: 0,
+            output_len: 0,
+        })
+    }
+
+    /// Feed input samples and append all output that is ready
+    pub fn push(&mut self, input: &[f32], output: &mut Vec<f32>) -> Result<(), String> {
+        self.input_len += input.len();
+        let Some(resampler) = self.inner.as_mut() else {
+            output.extend_from_slice(input);
+            self.output_len += input.len();
+            return Ok(());
+        };
+
+        self.pending.extend_from_slice(input);
+        let mut consumed = 0;
+        while self.pending.len() - consumed >= CHUNK {
+            let frames = resampler
+                .process(&[&self.pending[consumed..consumed + CHUNK]], None)
+                .map_err(|e| format!("Resampling fehlgeschlagen: {}", e))?;
+            consumed += CHUNK;
+            emit(&mut self.delay, &mut self.output_len, &frames[0], output);
+        }
+        self.pending.drain(..consumed);
+        Ok(())
+    }
+
+    /// Drain the remaining input and the filter delay at the end of the stream
+    pub fn flush(&mut self, output: &mut Vec<f32>) -> Result<(), String> {
+        let Some(resampler) = self.inner.as_mut() else {
+            return Ok(());
+        };
+        if self.input_len == 0 {
+            return Ok(());
+        }
+
+        let expected = (self.input_len as f64 * self.ratio) as usize;
+        if !self.pending.is_empty() {
+            let frames = resampler
+                .process_partial(Some(&[self.pending.as_slice()]), None)
+                .map_err(|e| format!("Resampling fehlgeschlagen: {}", e))?;
+            self.pending.clear();
+            emit(&mut self.delay, &mut self.output_len, &frames[0], output);
+        }
+
+        while self.output_len < expected {
+            let frames = resampler
+                .process_partial(None::<&[&[f32]]>, None)
+                .map_err(|e| format!("Resampling fehlgeschlagen: {}", e))?;
+            emit(&mut self.delay, &mut self.output_len, &frames[0], output);
+        }
+
+        // The last chunk was zero-padded: cut the output back to the exact length
+        let excess = self.output_len.saturating_sub(expected);
+        output.truncate(output.len().saturating_sub(excess));
+        self.output_len -= excess;
+        Ok(())
+    }
+}
+
+/// Append resampler output, skipping what is left of the initial filter delay
+fn emit(delay: &mut usize, output_len: &mut usize, frames: &[f32], output: &mut Vec<f32>) {
+    let skip = (*delay).min(frames.len());
+    *delay -= skip;
+    output.extend_from_slice(&frames[skip..]);
+    *output_len += frames.len() - skip;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sine(len: usize, rate: u32) -> Vec<f32> {
+        (0..len)
+            .map(|i| 0.5 * (2.0 * std::f32::consts::PI * 440.0 * i as f32 / rate as f32).sin())
+            .collect()
+    }
+
+    fn run(resampler: &mut StreamResampler, input: &[f32], pieces: &[usize]) -> Vec<f32> {
+        let mut output = Vec::new();
+        let mut rest = input;
+        for &piece in pieces.iter().cycle() {
+            if rest.is_empty() {
+                break;
+            }
+            let (head, tail) = rest.split_at(piece.min(rest.len()));
+            resampler.push(head, &mut output).unwrap();
+            rest = tail;
+        }
+        resampler.flush(&mut output).unwrap();
+        output
+    }
+
+    #[test]
+    fn output_length_follows_the_ratio_across_chunk_boundaries() {
+        for (from, len) in [
+            (48_000, 72_017),
+            (44_100, 44_100),
+            (8_000, 1_500),
+            (48_000, 10),
+        ] {
+            let input = sine(len, from);
+            let expected = (len as f64 * 16_000.0 / from as f64) as usize;
+
+            // Odd piece sizes straddle the internal 1024-sample chunks
+            let mut resampler = StreamResampler::new(from, 16_000).unwrap();
+            let pieced = run(&mut resampler, &input, &[1_000, 333, 7, 5_000]);
+            assert_eq!(pieced.len(), expected, "{} Hz, {} samples", from, len);
+
+            let mut resampler = StreamResampler::new(from, 16_000).unwrap();
+            let whole = run(&mut resampler, &input, &[len]);
+            assert_eq!(whole, pieced);
+        }
+    }
+
+    #[test]
+    fn delay_is_compensated() {
+        // A 440 Hz sine keeps its phase: output sample n is input time n / 16 kHz
+        let input = sine(48_000, 48_000);
+        let mut resampler = StreamResampler::new(48_000, 16_000).unwrap();
+        let output = run(&mut resampler, &input, &[4_096]);
+        let reference = sine(16_000, 16_000);
+        let error = output[1_000..15_000]
+            .iter()
+            .zip(&reference[1_000..15_000])
+            .map(|(a, b)| (a - b).abs())
+            .fold(0.0f32, f32::max);
+        // One sample off would be 0.086 at this frequency and amplitude
+        assert!(error < 0.05, "max deviation {}", error);
+    }
+
+    #[test]
+    fn same_rate_passes_through() {
+        let input = sine(5_000, 16_000);
+        let mut resampler = StreamResampler::new(16_000, 16_000).unwrap();
+        assert_eq!(run(&mut resampler, &input, &[777]), input);
+    }
+}
@@ -0,0 +1,319 @@
+//! Voice Activity Detection
+//!
+//! Classifies short PCM frames as speech or non-speech using frame energy, zero-crossing
+//! rate and an adaptive noise floor, then groups speech frames into segments.
+
+use std::ops::Range;
+
+use serde::{Deserialize, Serialize};
+
+use super::AudioBuffer;
+
+/// How fast the noise floor rises towards louder frames (per frame, 0..1).
+/// Slow enough for words to pass, fast enough to absorb a steady hum within a few seconds.
+/// Falling is instant so the floor snaps to pauses between words.
+const FLOOR_RISE_RATE: f32 = 0.005;
+
+/// Tunable VAD thresholds (persisted in the backend config)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct VadConfig {
+    /// Frame length in milliseconds (10-30)
+    pub frame_ms: u32,
+    /// Absolute minimum frame RMS (16-bit scale) to count as speech
+    pub energy_threshold: f32,
+    /// Frame RMS must exceed the noise floor by this factor
+    pub noise_margin: f32,
+    /// Frames with a higher zero-crossing rate (0..1) are treated as noise (hiss, fans)
+    pub max_zero_crossing_rate: f32,
+    /// Non-speech gap that still counts as part of the same segment
+    pub hangover_ms: u32,
+    /// Segments shorter than this are discarded (clicks, knocks)
+    pub min_segment_ms: u32,
+    /// Minimum total speech required to accept a recording
+    pub min_speech_ms: u32,
+    /// Minimum share of speech frames required to accept a recording (0..1)
+    pub min_speech_ratio: f32,
+}
+
+impl Default for VadConfig {
+    fn default() -> Self {
+        Self {
+            frame_ms: 20,
+            energy_threshold: 100.0,
+            noise_margin: 2.5,
+            max_zero_crossing_rate: 0.35,
+            hangover_ms: 200,
+            min_segment_ms: 60,
+            min_speech_ms: 300,
+            min_speech_ratio: 0.03,
+        }
+    }
+}
+
+/// Contiguous region of speech (sample offsets into the analyzed buffer)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SpeechSegment {
+    pub start: usize,
+    pub end: usize,
+}
+
+/// Result of analyzing a whole buffer
+#[derive(Debug, Clone)]
+pub struct VadResult {
+    /// Share of frames classified as speech (0..1)
+    pub speech_ratio: f32,
+    /// Total duration of all segments
+    pub speech_ms: u64,
+    pub segments: Vec<SpeechSegment>,
+    /// Final noise floor estimate (RMS, 16-bit scale)
+    pub noise_floor: f32,
+}
+
+impl VadResult {
+    /// Decide whether the recording contains enough speech to transcribe
+    pub fn has_speech(&self, config: &VadConfig) -> bool {
+        self.speech_ms >= config.min_speech_ms as u64
+            && self.speech_ratio >= config.min_speech_ratio
+    }
+
+    /// Sample range from the first to the last speech segment, extended by `padding`
+    /// samples on both sides and clamped to `len`
+    pub fn speech_span(&self, padding: usize, len: usize) -> Option<Range<usize>> {
+        let first = self.segments.first()?;
+        let last = self.segments.last()?;
+        Some(first.start.saturating_sub(padding)..(last.end + padding).min(len))
+    }
+}
+
+/// Frame-by-frame speech classifier with an adaptive noise floor.
+/// Usable on a complete buffer or on a live stream of frames.
+pub struct FrameClassifier {
+    config: VadConfig,
+    noise_floor: f32,
+}
+
+impl FrameClassifier {
+    pub fn new(config: VadConfig) -> Self {
+        // Start so that the initial speech threshold equals the absolute energy threshold
+        let noise_floor = config.energy_threshold / config.noise_margin.max(1.0);
+        Self {
+            config,
+            noise_floor,
+        }
+    }
+
+    /// Number of samples per frame at the given sample rate
+    pub fn frame_len(&self, sample_rate: u32) -> usize {
+        (sample_rate as usize * self.config.frame_ms.clamp(10, 30) as usize / 1000).max(1)
+    }
+
+    pub fn noise_floor(&self) -> f32 {
+        self.noise_floor
+    }
+
+    /// Classify one frame as speech and update the noise floor
+    pub fn classify(&mut self, frame: &[i16]) -> bool {
+        let rms = rms(frame);
+        let zero_crossing_rate = zero_crossing_rate(frame);
+
+        let threshold = self
+            .config
+            .energy_threshold
+            .max(self.noise_floor * self.config.noise_margin);
+        let is_speech =
+            rms >= threshold && zero_crossing_rate <= self.config.max_zero_crossing_rate;
+
+        if rms < self.noise_floor {
+            self.noise_floor = rms.max(1.0);
+        } else {
+            self.noise_floor += (rms - self.noise_floor) * FLOOR_RISE_RATE;
+        }
+
+        is_speech
+    }
+}
+
+/// Run the VAD over a complete buffer
+pub fn analyze(buffer: &AudioBuffer, config: &VadConfig) -> VadResult {
+    let mut classifier = FrameClassifier::new(config.clone());
+    let frame_len = classifier.frame_len(buffer.sample_rate);
+    let samples_per_ms = buffer.sample_rate as usize / 1000;
+    let hangover = config.hangover_ms as usize * samples_per_ms;
+    let min_segment = config.min_segment_ms as usize * samples_per_ms;
+
+    let mut frames = 0usize;
+    let mut speech_frames = 0usize;
+    let mut segments: Vec<SpeechSegment> = Vec::new();
+    let mut current: Option<SpeechSegment> = None;
+
+    for (index, frame) in buffer.samples.chunks(frame_len).enumerate() {
+        frames += 1;
+        let start = index * frame_len;
+        let end = start + frame.len();
+
+        if classifier.classify(frame) {
+            speech_frames += 1;
+            current = match current {
+                // Bridge short pauses within the hangover window
+                Some(segment) if start - segment.end <= hangover => {
+                    Some(SpeechSegment { end, ..segment })
+                }
+                Some(segment) => {
+                    segments.push(segment);
+                    Some(SpeechSegment { start, end })
+                }
+                None => Some(SpeechSegment { start, end }),
+            };
+        }
+    }
+    segments.extend(current);
+    segments.retain(|s| s.end - s.start >= min_segment);
+
+    let speech_samples: usize = segments.iter().map(|s| s.end - s.start).sum();
+
+    VadResult {
+        speech_ratio: if frames == 0 {
+            0.0
+        } else {
+            speech_frames as f32 / frames as f32
+        },
+        speech_ms: (speech_samples / samples_per_ms.max(1)) as u64,
+        segments,
+        noise_floor: classifier.noise_floor(),
+    }
+}
+
+/// Root mean square of a frame (16-bit scale)
+pub fn rms(frame: &[i16]) -> f32 {
+    if frame.is_empty() {
+        return 0.0;
+    }
+    let sum_squares: f64 = frame.iter().map(|&s| (s as f64).powi(2)).sum();
+    (sum_squares / frame.len() as f64).sqrt() as f32
+}
+
+/// Share of adjacent sample pairs that change sign (0..1)
+fn zero_crossing_rate(frame: &[i16]) -> f32 {
+    if frame.len() < 2 {
+        return 0.0;
+    }
+    let crossings = frame
+        .windows(2)
+        .filter(|pair| (pair[0] >= 0) != (pair[1] >= 0))
+        .count();
+    crossings as f32 / (frame.len() - 1) as f32
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const RATE: usize = 16_000;
+
+    fn tone(ms: usize, amplitude: f32) -> Vec<i16> {
+        (0..ms * RATE / 1000)
+            .map(|i| {
+                let t = i as f32 / RATE as f32;
+                (amplitude * (2.0 * std::f32::consts::PI * 220.0 * t).sin()) as i16
+            })
+            .collect()
+    }
+
+    /// Deterministic white noise (xorshift)
+    fn noise(ms: usize, amplitude: f32) -> Vec<i16> {
+        let mut state = 0x2545_f491_u32;
+        (0..ms * RATE / 1000)
+            .map(|_| {
+                state ^= state << 13;
+                state ^= state >> 17;
+                state ^= state << 5;
+                let unit = (state as f32 / u32::MAX as f32) * 2.0 - 1.0;
+                (unit * amplitude) as i16
+            })
+            .collect()
+    }
+
+    fn silence(ms: usize) -> Vec<i16> {
+        vec![0; ms * RATE / 1000]
+    }
+
+    fn analyze_samples(parts: &[Vec<i16>]) -> VadResult {
+        let buffer = AudioBuffer::new(parts.concat());
+        analyze(&buffer, &VadConfig::default())
+    }
+
+    #[test]
+    fn silence_is_rejected() {
+        let result = analyze_samples(&[silence(2000)]);
+        assert!(result.segments.is_empty());
+        assert!(!result.has_speech(&VadConfig::default()));
+    }
+
+    #[test]
+    fn speech_segment_boundaries_are_reported() {
+        let result = analyze_samples(&[silence(500), tone(1000, 3000.0), silence(500)]);
+        assert_eq!(result.segments.len(), 1);
+        let segment = result.segments[0];
+        assert_eq!(segment.start, 500 * RATE / 1000);
+        assert_eq!(segment.end, 1500 * RATE / 1000);
+        assert!((result.speech_ratio - 0.5).abs() < 0.05);
+        assert!(result.has_speech(&VadConfig::default()));
+    }
+
+    #[test]
+    fn speech_span_is_padded_and_clamped() {
+        let result = analyze_samples(&[silence(100), tone(500, 3000.0), silence(1000)]);
+        let padding = 300 * RATE / 1000;
+        let len = 1600 * RATE / 1000;
+        assert_eq!(result.speech_span(padding, len), Some(0..900 * RATE / 1000));
+        assert_eq!(
+            analyze_samples(&[silence(500)]).speech_span(padding, len),
+            None
+        );
+    }
+
+    #[test]
+    fn short_pauses_are_bridged_and_long_pauses_split() {
+        let result = analyze_samples(&[
+            tone(400, 3000.0),
+            silence(100),
+            tone(400, 3000.0),
+            silence(800),
+            tone(400, 3000.0),
+        ]);
+        assert_eq!(result.segments.len(), 2);
+    }
+
+    #[test]
+    fn single_cough_is_rejected() {
+        let result = analyze_samples(&[silence(1000), tone(150, 8000.0), silence(3000)]);
+        assert!(!result.has_speech(&VadConfig::default()));
+    }
+
+    #[test]
+    fn quiet_speaker_in_long_recording_is_accepted() {
+        let mut parts = vec![noise(500, 20.0)];
+        for _ in 0..10 {
+            parts.push(tone(600, 250.0));
+            parts.push(noise(1400, 20.0));
+        }
+        let result = analyze_samples(&parts);
+        assert!(result.has_speech(&VadConfig::default()));
+        assert_eq!(result.segments.len(), 10);
+    }
+
+    #[test]
+    fn broadband_noise_is_not_speech() {
+        let result = analyze_samples(&[noise(3000, 2000.0)]);
+        assert!(!result.has_speech(&VadConfig::default()));
+    }
+
+    #[test]
+    fn noise_floor_adapts_to_steady_hum() {
+        // A constant hum above the absolute threshold is only "speech" until the floor catches up
+        let result = analyze_samples(&[noise(200, 20.0), tone(5000, 200.0)]);
+        assert!(result.noise_floor > 100.0);
+        assert!(result.speech_ratio < 0.5);
+    }
+}
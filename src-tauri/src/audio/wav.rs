@@ -0,0 +1,76 @@
+//! WAV Writer
+//!
+//! Encodes an `AudioBuffer` as 16-bit PCM WAV (the input format whisper-cli expects).
+
+use super::AudioBuffer;
+
+/// Size of the canonical RIFF/WAVE header
+const HEADER_LEN: usize = 44;
+
+/// Encode the buffer as an in-memory WAV file
+pub fn encode(buffer: &AudioBuffer) -> Vec<u8> {
+    let data_len = (buffer.samples.len() * 2) as u32;
+    let byte_rate = buffer.sample_rate * 2;
+
+    let mut bytes = Vec::with_capacity(HEADER_LEN + data_len as usize);
+    bytes.extend_from_slice(b"RIFF");
+    bytes.extend_from_slice(&(HEADER_LEN as u32 - 8 + data_len).to_le_bytes());
+    bytes.extend_from_slice(b"WAVE");
+    bytes.extend_from_slice(b"fmt ");
+    bytes.extend_from_slice(&16u32.to_le_bytes()); // fmt chunk size
+    bytes.extend_from_slice(&1u16.to_le_bytes()); // PCM
+    bytes.extend_from_slice(&1u16.to_le_bytes()); // Mono
+    bytes.extend_from_slice(&buffer.sample_rate.to_le_bytes());
+    bytes.extend_from_slice(&byte_rate.to_le_bytes());
+    bytes.extend_from_slice(&2u16.to_le_bytes()); // Block align
+    bytes.extend_from_slice(&16u16.to_le_bytes()); // Bits per sample
+    bytes.extend_from_slice(b"data");
+    bytes.extend_from_slice(&data_len.to_le_bytes());
+    for sample in &buffer.samples {
+        bytes.extend_from_slice(&sample.to_le_bytes());
+    }
+
+    bytes
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn header_describes_the_samples() {
+        let samples: Vec<i16> = (0..1_601)
+            .map(|i| (i * 37 % 4_000 - 2_000) as i16)
+            .collect();
+        let bytes = encode(&AudioBuffer::new(samples.clone()));
+        let u32_at = |at: usize| u32::from_le_bytes(bytes[at..at + 4].try_into().unwrap());
+        let u16_at = |at: usize| u16::from_le_bytes(bytes[at..at + 2].try_into().unwrap());
+
+        assert_eq!(bytes.len(), HEADER_LEN + samples.len() * 2);
+        assert_eq!(&bytes[0..4], b"RIFF");
+        assert_eq!(u32_at(4) as usize, bytes.len() - 8);
+        assert_eq!(&bytes[8..16], b"WAVEfmt ");
+        assert_eq!((u16_at(20), u16_at(22)), (1, 1)); // PCM, mono
+        assert_eq!(u32_at(24), 16_000);
+        assert_eq!(u32_at(28), 32_000);
+        assert_eq!((u16_at(32), u16_at(34)), (2, 16));
+        assert_eq!(&bytes[36..40], b"data");
+        assert_eq!(u32_at(40) as usize, samples.len() * 2);
+    }
+
+    #[test]
+    fn decoder_reads_it_back() {
+        let samples: Vec<i16> = (0..16_000)
+            .map(|i| ((i % 200) * 150 - 15_000) as i16)
+            .collect();
+        let decoded =
+            super::super::decoder::decode(&encode(&AudioBuffer::new(samples.clone()))).unwrap();
+
+        assert_eq!(decoded.len(), samples.len());
+        // i16 → f32 → i16 may round by one step
+        assert!(decoded
+            .iter()
+            .zip(&samples)
+            .all(|(a, b)| (*a as i32 - *b as i32).abs() <= 1));
+    }
+}
@@ -0,0 +1,102 @@
+//! Backend Configuration
+//!
+//! Settings owned by the Rust backend, persisted as `config.json` in the app config directory.
+
+use std::path::PathBuf;
+
+use serde::{Deserialize, Serialize};
+use tauri::Manager;
+
+use crate::audio::{
+    chunk::ChunkConfig, dsp::DspConfig, endpoint::EndpointConfig, vad::VadConfig, TrimConfig,
+};
+use crate::stt::{hallucination::HallucinationConfig, SttConfig};
+use crate::wake::WakeConfig;
+use crate::watch::WatchConfig;
+
+const CONFIG_FILE: &str = "config.json";
+
+/// How the global hotkey controls recording
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum HotkeyMode {
+    /// Press to start, press again to stop
+    #[default]
+    Toggle,
+    /// Record while the hotkey is held; a short tap still toggles
+    PushToTalk,
+}
+
+/// Persistent backend settings
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AppConfig {
+    /// Silence Guard (VAD) thresholds
+    pub vad: VadConfig,
+    /// Silence trimming before STT
+    pub trim: TrimConfig,
+    /// Audio cleanup before VAD and STT (high-pass, noise suppression, normalization)
+    pub dsp: DspConfig,
+    /// Splitting of long recordings for parallel transcription
+    pub chunking: ChunkConfig,
+    /// Speech-to-text engine per mode
+    pub stt: SttConfig,
+    /// Removal of text Whisper invents for silence and noise
+    pub hallucination: HallucinationConfig,
+    /// Automatic end of recording (trailing silence, duration cap)
+    pub endpoint: EndpointConfig,
+    /// Strict in-memory mode: audio is only passed through pipes, never written to disk
+    pub in_memory_audio: bool,
+    /// Name of the microphone to record from, `None` = system default
+    pub input_device: Option<String>,
+    /// Alt+Space behavior
+    pub hotkey_mode: HotkeyMode,
+    /// Hands-free start by wake phrase (local STT only)
+    pub wake: WakeConfig,
+    /// Folder whose new audio files are transcribed to Markdown in the background
+    pub watch: WatchConfig,
+}
+
+fn config_path(app: &tauri::AppHandle) -> Result<PathBuf, String> {
+    app.path()
+        .app_config_dir()
+        .map(|dir| dir.join(CONFIG_FILE))
+        .map_err(|e| format!("Konfigurationsordner nicht gefunden: {}", e))
+}
+
+/// Load the config, falling back to defaults if it is missing or invalid
+pub fn load(app: &tauri::AppHandle) -> AppConfig {
+    let path = match config_path(app) {
+        Ok(path) => path,
+        Err(e) => {
+            eprintln!("⚠ {}. Using default config.", e);
+            return AppConfig::default();
+        }
+    };
+
+    match std::fs::read_to_string(&path) {
+        Ok(content) => serde_json::from_str(&content).unwrap_or_else(|e| {
+            eprintln!(
+                "⚠ Invalid config {}: {}. Using defaults.",
+                path.display(),
+                e
+            );
+            AppConfig::default()
+        }),
+        Err(_) => AppConfig::default(),
+    }
+}
+
+/// Write the config to disk
+pub fn save(app: &tauri::AppHandle, config: &AppConfig) -> Result<(), String> {
+    let path = config_path(app)?;
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir)
+            .map_err(|e| format!("Fehler beim Anlegen des Konfigurationsordners: {}", e))?;
+    }
+
+    let content = serde_json::to_string_pretty(config)
+        .map_err(|e| format!("Fehler beim Serialisieren der Konfiguration: {}", e))?;
+    std::fs::write(&path, content)
+        .map_err(|e| format!("Fehler beim Speichern der Konfiguration: {}", e))
+}
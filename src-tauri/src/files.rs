@@ -0,0 +1,125 @@
+//! File Import
+//!
+//! Existing recordings (phone voice memos, meeting exports, videos) run through the same
+//! STT → skill pipeline as live dictation. Results are returned or saved as Markdown with
+//! frontmatter (next to the source file or in an output folder) instead of being typed.
+
+use std::path::{Path, PathBuf};
+
+use chrono::{DateTime, Local, SecondsFormat};
+use serde::Serialize;
+
+use crate::PipelineSettings;
+
+/// Extensions the native decoder can read (audio files and the audio track of videos)
+pub const SUPPORTED_EXTENSIONS: &[&str] = &[
+    "wav", "mp3", "m4a", "aac", "ogg", "oga", "opus", "flac", "mp4", "mov", "mkv", "webm",
+];
+
+/// Result of `transcribe_file`
+#[derive(Debug, Clone, Serialize)]
+pub struct FileTranscription {
+    pub text: String,
+    /// Where the result was saved, if requested
+    pub saved_to: Option<PathBuf>,
+}
+
+/// Whether the file extension is one we can decode
+pub fn is_supported(path: &Path) -> bool {
+    path.extension()
+        .and_then(|e| e.to_str())
+        .map(|e| SUPPORTED_EXTENSIONS.contains(&e.to_lowercase().as_str()))
+        .unwrap_or(false)
+}
+
+/// Metadata block at the top of a saved transcript
+#[derive(Debug, Serialize)]
+pub struct Frontmatter {
+    /// Recording date (file modification time), RFC 3339
+    pub date: String,
+    pub source: String,
+    /// `hh:mm:ss`
+    pub duration: String,
+    pub engine: String,
+    pub skill: String,
+    /// Detected language, or the configured one if the engine reports none
+    pub language: String,
+}
+
+impl Frontmatter {
+    pub fn new(
+        source: &Path,
+        duration_ms: u64,
+        engine: String,
+        settings: &PipelineSettings,
+        detected_language: Option<&str>,
+    ) -> Self {
+        let date: DateTime<Local> = std::fs::metadata(source)
+            .and_then(|meta| meta.modified())
+            .map(DateTime::from)
+            .unwrap_or_else(|_| Local::now());
+        let seconds = duration_ms / 1000;
+
+        Self {
+            date: date.to_rfc3339_opts(SecondsFormat::Secs, false),
+            source: source
+                .file_name()
+                .map(|name| name.to_string_lossy().into_owned())
+                .unwrap_or_default(),
+            duration: format!(
+                "{:02}:{:02}:{:02}",
+                seconds / 3600,
+                seconds / 60 % 60,
+                seconds % 60
+            ),
+            engine,
+            skill: settings.skill.clone(),
+            language: detected_language
+                .map(str::to_string)
+                .unwrap_or_else(|| settings.language.clone()),
+        }
+    }
+}
+
+/// Markdown document: YAML frontmatter followed by the result
+pub fn render_markdown(frontmatter: &Frontmatter, text: &str) -> Result<String, String> {
+    let yaml = serde_yaml::to_string(frontmatter)
+        .map_err(|e| format!("Fehler beim Erstellen der Metadaten: {}", e))?;
+    Ok(format!("---\n{}---\n\n{}\n", yaml, text.trim()))
+}
+
+/// Write `<name>.md` next to the source (or into `output_dir`), never overwriting a file
+pub fn save_result(
+    source: &Path,
+    output_dir: Option<&Path>,
+    markdown: &str,
+) -> Result<PathBuf, String> {
+    let target = match output_dir {
+        Some(dir) => {
+            std::fs::create_dir_all(dir)
+                .map_err(|e| format!("Fehler beim Erstellen von {}: {}", dir.display(), e))?;
+            dir.join(source.file_name().unwrap_or_default())
+        }
+        None => source.to_path_buf(),
+    };
+    let path = free_path(&target, "md");
+    std::fs::write(&path, markdown)
+        .map_err(|e| format!("Fehler beim Speichern von {}: {}", path.display(), e))?;
+    Ok(path)
+}
+
+/// `<stem>.<ext>`, or `<stem> (2).<ext>`, `<stem> (3).<ext>`, ... if taken
+fn free_path(source: &Path, extension: &str) -> PathBuf {
+    let stem = source
+        .file_stem()
+        .map(|s| s.to_string_lossy().into_owned())
+        .unwrap_or_else(|| "transcript".to_string());
+
+    let mut path = source.with_file_name(format!("{}.{}", stem, extension));
+    let mut counter = 2;
+    while path.exists() {
+        path = source.with_file_name(format!("{} ({}).{}", stem, counter, extension));
+        counter += 1;
+    }
+    path
+}
@@ -1,4 +1,5 @@
 use std::sync::Mutex;
+use std::time::{Duration, Instant};
 use tauri::{
     menu::{Menu, MenuItem},
     tray::{MouseButton, MouseButtonState, TrayIconBuilder, TrayIconEvent},
@@ -6,12 +7,80 @@ use tauri::{
 };
 use tauri_plugin_global_shortcut::{Code, GlobalShortcutExt, Modifiers, Shortcut, ShortcutState};
 
+mod audio;
+mod config;
+mod files;
 mod input;
 mod llm;
+mod pipe;
+mod session;
+mod stt;
+mod wake;
+mod watch;
+mod workspace;
+
+use config::{AppConfig, HotkeyMode};
 
 /// Application state holding loaded skills
 pub struct AppState {
-    pub is_recording: bool,
+    /// Active native microphone capture, `None` while idle
+    pub recording: Option<audio::capture::Recording>,
+    /// Active continuous dictation session (owns no audio, `recording` is its capture)
+    pub session: Option<session::Session>,
+    /// When the hotkey press that opened the window started (push-to-talk only)
+    pub hotkey_pressed_at: Option<Instant>,
+    /// Background wake phrase listener, `None` while disabled
+    pub wake: Option<wake::WakeListener>,
+    /// Background watch folder, `None` while disabled
+    pub watcher: Option<watch::FolderWatcher>,
+    /// STT engine resources shared across requests (e.g. the whisper-server process)
+    pub engines: stt::SharedEngines,
+    pub config: AppConfig,
+}
+
+/// ID of the system tray icon
+const TRAY_ID: &str = "main";
+
+/// Holding the hotkey at least this long counts as push-to-talk instead of a tap
+const PUSH_TO_TALK_HOLD: Duration = Duration::from_millis(300);
+
+/// Per-request STT/LLM settings sent by the frontend
+#[derive(Clone)]
+pub struct PipelineSettings {
+    privacy_mode: bool,
+    whisper_path: String,
+    model_path: String,
+    language: String,
+    skill: String,
+}
+
+impl PipelineSettings {
+    /// STT engine selected in the config for the current mode
+    fn stt_engine(
+        &self,
+        app_handle: &tauri::AppHandle,
+    ) -> Result<std::sync::Arc<dyn stt::SpeechToText>, String> {
+        let (stt_config, in_memory_audio, shared) = app_handle
+            .state::<Mutex<AppState>>()
+            .lock()
+            .map(|s| {
+                (
+                    s.config.stt.clone(),
+                    s.config.in_memory_audio,
+                    s.engines.clone(),
+                )
+            })
+            .map_err(|e| e.to_string())?;
+        let id = stt_config.engine_id(self.privacy_mode).to_string();
+        let context = stt::EngineContext {
+            whisper_path: self.whisper_path.clone(),
+            model_path: self.model_path.clone(),
+            in_memory_audio,
+            config: stt_config,
+            shared,
+        };
+        stt::Registry::builtin().create(&id, &context)
+    }
 }
 
 /// Force window to topmost using Windows native API
@@ -59,6 +128,17 @@ fn force_topmost_native(_window: &tauri::WebviewWindow) {}
 #[cfg(not(windows))]
 fn force_topmost_window(_window: &tauri::Window) {}
 
+/// Show the HUD without taking focus from the app being typed into
+#[cfg(windows)]
+fn show_window_inactive(window: &tauri::WebviewWindow) {
+    force_topmost_native(window); // SWP_SHOWWINDOW | SWP_NOACTIVATE
+}
+
+#[cfg(not(windows))]
+fn show_window_inactive(window: &tauri::WebviewWindow) {
+    let _ = window.show();
+}
+
 /// Position window at bottom-center of the screen
 fn position_window_bottom_center(window: &tauri::WebviewWindow) {
     if let Ok(Some(monitor)) = window.current_monitor() {
@@ -108,56 +188,434 @@ fn toggle_window(app: &tauri::AppHandle) {
     }
 }
 
-/// Check if audio is silent based on RMS threshold (16-bit PCM)
-fn is_silent(audio_samples: &[i16], threshold: f32) -> bool {
-    if audio_samples.is_empty() {
-        return true;
+/// Alt+Space: toggles the window on press. In push-to-talk mode, releasing the key
+/// after holding it closes the window again, which stops the recording and processes it.
+fn handle_hotkey(app: &tauri::AppHandle, key_state: ShortcutState) {
+    let state = app.state::<Mutex<AppState>>();
+    let Ok(mut state) = state.lock() else {
+        return;
+    };
+
+    match key_state {
+        ShortcutState::Pressed => {
+            // Ignore key repeat while held
+            if state.hotkey_pressed_at.is_some() {
+                return;
+            }
+
+            // The HUD may be hidden while a session types, so end it regardless of visibility
+            if state.session.is_some() {
+                drop(state);
+                let _ = app.emit("window-hiding", ());
+                if let Some(window) = app.get_webview_window("main") {
+                    let _ = window.hide();
+                }
+                return;
+            }
+
+            let opens_window = app
+                .get_webview_window("main")
+                .and_then(|window| window.is_visible().ok())
+                == Some(false);
+            if opens_window && state.config.hotkey_mode == HotkeyMode::PushToTalk {
+                state.hotkey_pressed_at = Some(Instant::now());
+            }
+            drop(state);
+            toggle_window(app);
+        }
+        ShortcutState::Released => {
+            let held = state.hotkey_pressed_at.take().map(|at| at.elapsed());
+            drop(state);
+            // A short tap keeps the window open, like in toggle mode
+            if held.is_some_and(|held| held >= PUSH_TO_TALK_HOLD) {
+                toggle_window(app);
+            }
+        }
     }
+}
 
-    let mut sum_squares = 0.0;
-    for &sample in audio_samples {
-        sum_squares += (sample as f32).powi(2);
+/// Show live speech activity in the tray tooltip
+fn set_tray_activity(app: &tauri::AppHandle, speaking: bool) {
+    if let Some(tray) = app.tray_by_id(TRAY_ID) {
+        let tooltip = if speaking {
+            "Voice Intelligence – 🎙️ Speaking"
+        } else {
+            "Voice Intelligence"
+        };
+        let _ = tray.set_tooltip(Some(tooltip));
     }
-    let mean_square = sum_squares / audio_samples.len() as f32;
-    let rms = mean_square.sqrt();
+}
 
-    rms < threshold
+/// Live levels for the HUD visualizer (~20 events/s), speech activity for the tray
+fn level_reporter(app: tauri::AppHandle) -> impl FnMut(audio::level::AudioLevel) + Send {
+    let mut was_speaking = false;
+    move |level| {
+        let _ = app.emit("audio-level", level);
+        if level.is_speech != was_speaking {
+            was_speaking = level.is_speech;
+            set_tray_activity(&app, was_speaking);
+        }
+    }
 }
 
-/// Check if text matches known hallucinations
-fn is_hallucination(text: &str) -> bool {
-    let clean_text = text.trim();
-    if clean_text.len() < 2 {
-        return true;
+/// Log the weakest segment values the engine reported (nothing for text-only engines)
+fn log_confidence(transcript: &stt::Transcript) {
+    let segments = &transcript.segments;
+    if segments.is_empty() {
+        return;
     }
+    let lowest_logprob = segments
+        .iter()
+        .filter_map(|s| s.avg_logprob)
+        .reduce(f32::min);
+    let highest_no_speech = segments
+        .iter()
+        .filter_map(|s| s.no_speech_prob)
+        .reduce(f32::max);
+    let highest_ratio = segments
+        .iter()
+        .filter_map(|s| s.compression_ratio)
+        .reduce(f32::max);
+    let show = |value: Option<f32>| value.map_or("-".to_string(), |v| format!("{:.2}", v));
+    println!(
+        "📊 {} segments | min avg_logprob {} | max no_speech_prob {} | max compression_ratio {}",
+        segments.len(),
+        show(lowest_logprob),
+        show(highest_no_speech),
+        show(highest_ratio)
+    );
+}
+
+/// Start native microphone capture (16 kHz mono PCM, kept in AppState)
+#[tauri::command]
+async fn start_recording(
+    app: tauri::AppHandle,
+    state: tauri::State<'_, Mutex<AppState>>,
+) -> Result<(), String> {
+    let (device, vad_config, endpoint_config) = {
+        let state = state.lock().map_err(|e| e.to_string())?;
+        if state.recording.is_some() {
+            return Err("Aufnahme läuft bereits".to_string());
+        }
+        (
+            state.config.input_device.clone(),
+            state.config.vad.clone(),
+            state.config.endpoint.clone(),
+        )
+    };
+
+    let meter = audio::level::LevelMeter::new(vad_config);
+    let mut endpointer = audio::endpoint::Endpointer::new(endpoint_config);
+    let mut report_level = level_reporter(app.clone());
+
+    let on_level = move |level: audio::level::AudioLevel| {
+        report_level(level);
+
+        // Endpointing: the frontend stops the recording like a manual stop
+        if let Some(reason) = endpointer.push(&level) {
+            println!("⏹️ Auto-stop: {:?}", reason);
+            let _ = app.emit("recording-auto-stop", reason);
+        }
+    };
+
+    // Opening the device can block for a while; the hotkey handler and other commands
+    // need the state in the meantime, so the lock is only taken to store the handle
+    let recording = tauri::async_runtime::spawn_blocking(move || {
+        audio::capture::Recording::start(device, meter, on_level)
+    })
+    .await
+    .map_err(|e| e.to_string())??;
+
+    let mut state = state.lock().map_err(|e| e.to_string())?;
+    if state.recording.is_none() {
+        state.recording = Some(recording);
+        return Ok(());
+    }
+    // Another start won the race while the device was opening
+    drop(state);
+    recording.cancel();
+    Err("Aufnahme läuft bereits".to_string())
+}
+
+/// List the available microphones
+#[tauri::command]
+async fn list_input_devices() -> Result<Vec<audio::capture::InputDevice>, String> {
+    audio::capture::list_devices()
+}
+
+/// Stop native capture and run the recorded audio through STT and the LLM
+#[tauri::command]
+async fn stop_recording(
+    privacy_mode: bool,
+    whisper_path: String,
+    model_path: String,
+    language: String,
+    skill: String,
+    app_handle: tauri::AppHandle,
+    state: tauri::State<'_, Mutex<AppState>>,
+) -> Result<String, String> {
+    let recording = state
+        .lock()
+        .map_err(|e| e.to_string())?
+        .recording
+        .take()
+        .ok_or("Keine laufende Aufnahme")?;
+
+    let duration_ms = recording.elapsed_ms();
+    let buffer = recording.stop()?;
+    set_tray_activity(&app_handle, false);
+
+    println!(
+        "🎤 Input: {}ms | Language: {} | Active Skill: {}",
+        duration_ms, language, skill
+    );
+
+    let settings = PipelineSettings {
+        privacy_mode,
+        whisper_path,
+        model_path,
+        language,
+        skill,
+    };
+    run_pipeline(Ok(buffer), None, settings, &app_handle).await
+}
+
+/// Stop native capture and discard the audio
+#[tauri::command]
+async fn cancel_recording(
+    app: tauri::AppHandle,
+    state: tauri::State<'_, Mutex<AppState>>,
+) -> Result<(), String> {
+    let recording = state.lock().map_err(|e| e.to_string())?.recording.take();
+    if let Some(recording) = recording {
+        recording.cancel();
+        set_tray_activity(&app, false);
+        println!("🚫 Recording cancelled");
+    }
+    Ok(())
+}
+
+/// Start continuous dictation: utterances are split at pauses and typed as they finish
+#[tauri::command]
+async fn start_session(
+    privacy_mode: bool,
+    whisper_path: String,
+    model_path: String,
+    language: String,
+    skill: String,
+    app_handle: tauri::AppHandle,
+    state: tauri::State<'_, Mutex<AppState>>,
+) -> Result<(), String> {
+    let (device, vad_config, silence_ms) = {
+        let state = state.lock().map_err(|e| e.to_string())?;
+        if state.recording.is_some() {
+            return Err("Aufnahme läuft bereits".to_string());
+        }
+        (
+            state.config.input_device.clone(),
+            state.config.vad.clone(),
+            state.config.endpoint.silence_ms,
+        )
+    };
+
+    // Like start_recording: the device opens without the state lock held
+    let app = app_handle.clone();
+    let (recording, pauses) = tauri::async_runtime::spawn_blocking(move || {
+        session::Session::open(&app, device, vad_config, silence_ms)
+    })
+    .await
+    .map_err(|e| e.to_string())??;
+
+    let mut state = state.lock().map_err(|e| e.to_string())?;
+    if state.recording.is_some() {
+        drop(state);
+        recording.cancel();
+        return Err("Aufnahme läuft bereits".to_string());
+    }
+    let settings = PipelineSettings {
+        privacy_mode,
+        whisper_path,
+        model_path,
+        language,
+        skill,
+    };
+    state.recording = Some(recording);
+    state.session = Some(session::Session::start(&app_handle, settings, pauses));
+    Ok(())
+}
+
+/// End continuous dictation and wait until the last utterance is typed.
+/// With `discard` the audio since the last pause is dropped instead of processed.
+#[tauri::command]
+async fn stop_session(
+    discard: bool,
+    app_handle: tauri::AppHandle,
+    state: tauri::State<'_, Mutex<AppState>>,
+) -> Result<(), String> {
+    let (session, recording) = {
+        let mut state = state.lock().map_err(|e| e.to_string())?;
+        (state.session.take(), state.recording.take())
+    };
+    let session = session.ok_or("Keine laufende Diktat-Sitzung")?;
+
+    // Audio since the last pause is the final utterance
+    let rest = match recording {
+        Some(recording) if !discard => recording.stop()?,
+        Some(recording) => {
+            recording.cancel();
+            audio::AudioBuffer::new(Vec::new())
+        }
+        None => audio::AudioBuffer::new(Vec::new()),
+    };
+    set_tray_activity(&app_handle, false);
+
+    session.finish(rest).await;
+    let _ = app_handle.emit("processing-complete", ());
+    Ok(())
+}
+
+/// Transcribe an existing audio or video file with the same STT → skill pipeline.
+/// The result is returned (and optionally saved next to the file), never typed.
+#[tauri::command]
+#[allow(clippy::too_many_arguments)]
+async fn transcribe_file(
+    path: String,
+    save: bool,
+    privacy_mode: bool,
+    whisper_path: String,
+    model_path: String,
+    language: String,
+    skill: String,
+    app_handle: tauri::AppHandle,
+) -> Result<files::FileTranscription, String> {
+    let path = std::path::PathBuf::from(path);
+    if !files::is_supported(&path) {
+        return Err(format!(
+            "Nicht unterstütztes Dateiformat: {}",
+            path.display()
+        ));
+    }
+    println!(
+        "📂 File: {} | Language: {} | Active Skill: {}",
+        path.display(),
+        language,
+        skill
+    );
+
+    // Decoding an hour-long file is CPU work, keep it off the async runtime
+    let decode_path = path.clone();
+    let decoded =
+        tauri::async_runtime::spawn_blocking(move || audio::AudioBuffer::decode_file(&decode_path))
+            .await
+            .map_err(|e| e.to_string())??;
+
+    let settings = PipelineSettings {
+        privacy_mode,
+        whisper_path,
+        model_path,
+        language,
+        skill,
+    };
+    let engine = settings.stt_engine(&app_handle)?.name();
+    let duration_ms = decoded.duration_ms();
+    let response = transcribe_and_respond(Ok(decoded), None, &settings, &app_handle).await?;
+    let frontmatter = files::Frontmatter::new(
+        &path,
+        duration_ms,
+        engine,
+        &settings,
+        response.language.as_deref(),
+    );
+    let text = response.text;
+
+    let saved_to = if save && !text.is_empty() {
+        let markdown = files::render_markdown(&frontmatter, &text)?;
+        let saved = files::save_result(&path, None, &markdown)?;
+        println!("💾 Result saved: {}", saved.display());
+        Some(saved)
+    } else {
+        None
+    };
 
-    let hallucinations = [
-        "Thank you.",
-        "Thank you for watching",
-        "Subtitles by",
-        "Thanks.",
-        "MBC",
-        "Untertitel der Amara.org-Community",
-        "Sous-titres réalisés par",
-        "Lädt...",
-        "Vielen Dank.",
-        "Vielen Dank für Ihre Aufmerksamkeit.",
-    ];
+    Ok(files::FileTranscription { text, saved_to })
+}
 
-    // Case-insensitive check? The user gave specific strings with punctuation.
-    // Let's do a fast "contains" or "equals" check.
-    // Given Whisper hallucinations are often EXACT lines, we check both.
-    for &h in &hallucinations {
-        if clean_text.contains(h) {
-            return true;
+/// (Re)start the wake phrase listener to match the config, or stop it when disabled.
+/// Always uses the Local Mode STT engine, independent of Cloud/Privacy mode.
+#[tauri::command]
+async fn sync_wake_listener(
+    whisper_path: String,
+    model_path: String,
+    app: tauri::AppHandle,
+    state: tauri::State<'_, Mutex<AppState>>,
+) -> Result<bool, String> {
+    // Stop outside the lock: the listener thread briefly locks the state itself
+    let previous = state.lock().map_err(|e| e.to_string())?.wake.take();
+    if let Some(listener) = previous {
+        listener.stop();
+    }
+
+    let (config, engines) = {
+        let state = state.lock().map_err(|e| e.to_string())?;
+        if !state.config.wake.enabled || state.config.wake.phrases.is_empty() {
+            return Ok(false);
         }
+        (state.config.clone(), state.engines.clone())
+    };
+
+    // Like start_recording: the device opens without the state lock held
+    let listener = tauri::async_runtime::spawn_blocking(move || {
+        wake::WakeListener::start(app, config, engines, whisper_path, model_path)
+    })
+    .await
+    .map_err(|e| e.to_string())??;
+
+    // A sync that ran in parallel may have stored its listener in the meantime
+    let replaced = state
+        .lock()
+        .map_err(|e| e.to_string())?
+        .wake
+        .replace(listener);
+    if let Some(listener) = replaced {
+        listener.stop();
+    }
+    Ok(true)
+}
+
+/// (Re)start the watch folder with the current STT/skill settings, or stop it when disabled
+#[tauri::command]
+async fn sync_folder_watcher(
+    privacy_mode: bool,
+    whisper_path: String,
+    model_path: String,
+    language: String,
+    skill: String,
+    app: tauri::AppHandle,
+    state: tauri::State<'_, Mutex<AppState>>,
+) -> Result<bool, String> {
+    let mut state = state.lock().map_err(|e| e.to_string())?;
+    if let Some(watcher) = state.watcher.take() {
+        watcher.stop();
+    }
+    if !state.config.watch.enabled {
+        return Ok(false);
     }
 
-    false
+    let settings = PipelineSettings {
+        privacy_mode,
+        whisper_path,
+        model_path,
+        language,
+        skill,
+    };
+    let watcher = watch::FolderWatcher::start(app, state.config.watch.clone(), settings)?;
+    state.watcher = Some(watcher);
+    Ok(true)
 }
 
 /// Process audio bytes from frontend - sends to Groq Whisper API, then to Llama3 for response
 #[tauri::command]
+#[allow(clippy::too_many_arguments)]
 async fn process_audio(
     audio_data: Vec<u8>,
     duration_ms: u64,
@@ -181,184 +639,280 @@ async fn process_audio(
         return Err("Keine Audiodaten empfangen".to_string());
     }
 
-    // Language Logic
-    // Cloud Whisper: None = Auto-Detect
-    // Local Whisper: "auto" = Auto-Detect (otherwise defaults to English on some builds)
-    let (target_lang_cloud, target_lang_local) = if language == "auto" {
-        (None, "auto".to_string())
-    } else {
-        (Some(language.clone()), language.clone())
+    // Decode once to 16kHz Mono PCM - shared by VAD and STT
+    let decoded = audio::AudioBuffer::decode(&audio_data, &ffmpeg_path);
+
+    let settings = PipelineSettings {
+        privacy_mode,
+        whisper_path,
+        model_path,
+        language,
+        skill,
     };
+    run_pipeline(decoded, Some(audio_data), settings, &app_handle).await
+}
 
-    // === PHASE 9: SILENCE GUARD (VAD Lite) ===
-    // 1. Write audio to temp file (universally needed for VAD)
-    let temp_dir = std::env::temp_dir();
-    let audio_path = temp_dir.join("voice_intelligence_temp_audio.webm");
-    let wav_path = temp_dir.join("voice_intelligence_temp_audio.wav");
+/// Keep original and processed audio in the app data dir for A/B listening
+fn save_dsp_comparison(
+    app: &tauri::AppHandle,
+    in_memory_audio: bool,
+    original: &audio::AudioBuffer,
+    processed: &audio::AudioBuffer,
+) {
+    if in_memory_audio {
+        println!("⚠ Strict RAM mode: processed audio is not saved.");
+        return;
+    }
+    let saved = app
+        .path()
+        .app_data_dir()
+        .map_err(|e| e.to_string())
+        .and_then(|dir| audio::dsp::save_comparison(&dir.join("processed"), original, processed));
+    match saved {
+        Ok(path) => println!("💾 Processed audio saved: {}", path.display()),
+        Err(e) => eprintln!("⚠ Could not save processed audio: {}", e),
+    }
+}
 
-    // std::fs::write(&audio_path, &audio_data)...
-    if let Err(e) = std::fs::write(&audio_path, &audio_data) {
-        eprintln!("❌ Failed to write temp audio for VAD: {}", e);
-        return Err(e.to_string());
+/// Shared pipeline: Silence Guard → STT → LLM → text injection.
+/// `original` is the compressed recording from the frontend, if there is one.
+async fn run_pipeline(
+    decoded: Result<audio::AudioBuffer, String>,
+    original: Option<Vec<u8>>,
+    settings: PipelineSettings,
+    app_handle: &tauri::AppHandle,
+) -> Result<String, String> {
+    let response_text = transcribe_and_respond(decoded, original, &settings, app_handle)
+        .await?
+        .text;
+    if response_text.is_empty() {
+        return Ok(response_text);
     }
 
-    // 2. Decode WebM to 16kHz Mono PCM S16LE via ffmpeg to STDOUT
-    // Use configured path or default (we need to be able to run this!)
-    let ffmpeg_cmd = if ffmpeg_path.is_empty() {
-        "ffmpeg"
+    deliver_response(app_handle, &response_text);
+
+    // Step 9: Emit completion event to frontend
+    let _ = app_handle.emit("processing-complete", ());
+
+    Ok(response_text)
+}
+
+/// Result of `transcribe_and_respond`
+#[derive(Debug, Default)]
+struct Response {
+    /// Skill output; empty when there is nothing to type (no speech, hallucination)
+    text: String,
+    /// Language the STT engine detected, if it reports one
+    language: Option<String>,
+}
+
+/// Audio cleanup → Silence Guard → STT → skill (LLM)
+async fn transcribe_and_respond(
+    decoded: Result<audio::AudioBuffer, String>,
+    original: Option<Vec<u8>>,
+    settings: &PipelineSettings,
+    app_handle: &tauri::AppHandle,
+) -> Result<Response, String> {
+    let state = app_handle.state::<Mutex<AppState>>();
+    let PipelineSettings {
+        privacy_mode,
+        language,
+        skill,
+        ..
+    } = settings;
+    let privacy_mode = *privacy_mode;
+
+    // Language Logic: None = Auto-Detect (engines translate this for their backend)
+    let target_lang = if language == "auto" {
+        None
     } else {
-        &ffmpeg_path
+        Some(language.clone())
     };
 
-    let vad_output = std::process::Command::new(ffmpeg_cmd)
-        .arg("-i")
-        .arg(&audio_path)
-        .arg("-ar")
-        .arg("16000") // 16 kHz
-        .arg("-ac")
-        .arg("1") // Mono
-        .arg("-f")
-        .arg("s16le") // Raw PCM
-        .arg("-") // Pipe to stdout
-        .output();
-
-    match vad_output {
-        Ok(output) if output.status.success() => {
-            let pcm_bytes = output.stdout;
-            // Convert bytes to i16
-            let pcm_samples: Vec<i16> = pcm_bytes
-                .chunks_exact(2)
-                .map(|chunk| i16::from_le_bytes([chunk[0], chunk[1]]))
-                .collect();
-
-            // Threshold: 100-300 recommended. Let's start with 150.
-            if is_silent(&pcm_samples, 150.0) {
-                println!("🔇 Silence detected (RMS < 150). Aborting.");
-                // Cleanup
-                let _ = std::fs::remove_file(&audio_path);
-                let _ = std::fs::remove_file(&wav_path);
+    let (vad_config, trim_config, dsp_config, chunk_config, hallucination_config, in_memory_audio) =
+        state
+            .lock()
+            .map(|s| {
+                (
+                    s.config.vad.clone(),
+                    s.config.trim.clone(),
+                    s.config.dsp.clone(),
+                    s.config.chunking.clone(),
+                    s.config.hallucination.clone(),
+                    s.config.in_memory_audio,
+                )
+            })
+            .unwrap_or_default();
+
+    // === PHASE 11: AUDIO CLEANUP (DSP) ===
+    // Before the Silence Guard: normalized levels keep its RMS thresholds meaningful
+    let processed = dsp_config.is_active() && decoded.is_ok();
+    let decoded = match decoded {
+        Ok(buffer) if processed => {
+            // FFT noise suppression over a long recording is CPU work, keep it off the async runtime
+            let app = app_handle.clone();
+            tauri::async_runtime::spawn_blocking(move || {
+                let cleaned = audio::dsp::process(&buffer, &dsp_config);
+                println!(
+                    "🎛️ DSP: high-pass {} | noise suppression {} | normalize {:?}",
+                    dsp_config.high_pass, dsp_config.noise_suppression, dsp_config.normalize
+                );
+                if dsp_config.save_processed {
+                    save_dsp_comparison(&app, in_memory_audio, &buffer, &cleaned);
+                }
+                cleaned
+            })
+            .await
+            .map_err(|e| e.to_string())
+        }
+        other => other,
+    };
+
+    // === PHASE 9: SILENCE GUARD (VAD) ===
+    let decoded = match decoded {
+        Ok(buffer) => {
+            let vad = audio::vad::analyze(&buffer, &vad_config);
+            println!(
+                "🎚️ VAD: {}ms audio | {}ms speech ({:.0}%) | {} segment(s) | noise floor {:.0}",
+                buffer.duration_ms(),
+                vad.speech_ms,
+                vad.speech_ratio * 100.0,
+                vad.segments.len(),
+                vad.noise_floor
+            );
+
+            if !vad.has_speech(&vad_config) {
+                println!("🔇 No speech detected. Aborting.");
                 // Clean return - frontend ignores empty string? Or we assume so.
-                return Ok("".to_string());
-            } else {
+                return Ok(Response::default());
+            }
+
+            // === PHASE 10: SILENCE TRIM ===
+            // Cut to the speech span (+ padding): silent edges are where Whisper hallucinates
+            let padding = trim_config.padding_ms as usize * buffer.sample_rate as usize / 1000;
+            match vad.speech_span(padding, buffer.samples.len()) {
+                Some(span) if trim_config.enabled => {
+                    let trimmed = buffer.slice(span);
+                    println!(
+                        "✂️ Trimmed: {}ms → {}ms",
+                        buffer.duration_ms(),
+                        trimmed.duration_ms()
+                    );
+                    Ok(trimmed)
+                }
+                _ => Ok(buffer),
             }
         }
         Err(e) => {
             eprintln!(
-                "⚠ VAD Failed (ffmpeg error): {}. Proceeding without Silence Guard.",
+                "⚠ VAD Failed (decoder error): {}. Proceeding without Silence Guard.",
                 e
             );
+            Err(e)
         }
-        Ok(_) => {}
-    }
+    };
 
     // Step 1: STT
-    let transcription = if privacy_mode {
-        println!("🛡️ STT (Local): {}", model_path);
-
-        // Validation
-        if whisper_path.is_empty() || model_path.is_empty() {
-            eprintln!("❌ Error: Local Whisper configuration missing!");
-            return Err("Settings error: Local Whisper binaries not configured. Please check 'Local Mode' in settings.".to_string());
+    let engine = match settings.stt_engine(app_handle) {
+        Ok(engine) => engine,
+        Err(e) => {
+            eprintln!("❌ Error: STT engine not available: {}", e);
+            return Err(e);
         }
+    };
+    let mode_label = if engine.is_local() {
+        "🛡️ STT (Local)"
+    } else {
+        "☁️ STT (Cloud)"
+    };
 
-        // 1. File is already written at logic start!
-        // std::fs::write(&audio_path, &audio_data) ... (Done above)
+    // Length of what STT hears (after trimming), for the hallucination filter
+    let clip_ms = decoded.as_ref().ok().map(|buffer| buffer.duration_ms());
+
+    // Long recordings are split at pauses and transcribed in parallel chunks
+    let long_recording = decoded
+        .as_ref()
+        .ok()
+        .filter(|buffer| audio::chunk::needs_chunking(buffer, &chunk_config));
+    let transcript = if let Some(buffer) = long_recording {
+        let segments = audio::vad::analyze(buffer, &vad_config).segments;
+        let chunks = audio::chunk::plan(buffer, &segments, &chunk_config);
+        println!(
+            "{} (Chunked): {} | {}ms in {} chunks, {} in parallel",
+            mode_label,
+            engine.name(),
+            buffer.duration_ms(),
+            chunks.len(),
+            chunk_config.concurrency
+        );
 
-        // 2. Convert WebM to WAV (16kHz) using ffmpeg
-        let ffmpeg_cmd = if ffmpeg_path.is_empty() {
-            "ffmpeg"
-        } else {
-            &ffmpeg_path
+        let progress_app = app_handle.clone();
+        stt::chunked::transcribe(
+            engine.clone(),
+            buffer,
+            &chunks,
+            target_lang.clone(),
+            chunk_config.concurrency,
+            move |progress| {
+                let _ = progress_app.emit("transcription-progress", progress);
+            },
+        )
+        .await
+    } else {
+        println!("{}: {}", mode_label, engine.name());
+
+        // Local engines get the already decoded PCM. Cloud engines get the trimmed clip
+        // (re-encoded as Opus by the engine), or the original recording if nothing was cut.
+        // Native captures have no original, so they are always sent as PCM.
+        let audio = match (decoded, original) {
+            (Ok(_), Some(original)) if !engine.is_local() && !trim_config.enabled && !processed => {
+                stt::AudioInput::Encoded(audio::EncodedAudio::webm(original))
+            }
+            (Ok(buffer), _) => stt::AudioInput::Pcm(buffer),
+            (Err(_), Some(original)) if !engine.is_local() => {
+                stt::AudioInput::Encoded(audio::EncodedAudio::webm(original))
+            }
+            (Err(e), _) => return Err(e),
         };
 
-        // Quietly convert...
-        let ffmpeg_output = std::process::Command::new(ffmpeg_cmd)
-            .arg("-y") // Overwrite
-            .arg("-i")
-            .arg(&audio_path)
-            .arg("-ar")
-            .arg("16000") // 16 kHz
-            .arg("-ac")
-            .arg("1") // Mono
-            .arg("-c:a")
-            .arg("pcm_s16le") // Signed 16-bit little endian
-            .arg(&wav_path)
-            .output()
-            .map_err(|e| format!("Fehler beim Ausführen von ffmpeg: {}", e))?;
-
-        if !ffmpeg_output.status.success() {
-            let err_msg = String::from_utf8_lossy(&ffmpeg_output.stderr);
-            return Err(format!("FFmpeg Konvertierungsfehler: {}", err_msg));
-        }
-
-        // 3. Run Whisper CLI
-        let mut whisper_cmd = std::process::Command::new(&whisper_path);
-        whisper_cmd
-            .arg("-m")
-            .arg(&model_path)
-            .arg("-f")
-            .arg(&wav_path)
-            .arg("--no-timestamps")
-            .arg("-l")
-            .arg(&target_lang_local); // Always pass language (explicit or auto)
-
-        let whisper_output = whisper_cmd
-            .output()
-            .map_err(|e| format!("Fehler beim Ausführen von {}: {}", whisper_path, e))?;
-
-        if !whisper_output.status.success() {
-            let err_msg = String::from_utf8_lossy(&whisper_output.stderr);
-            return Err(format!("Whisper CLI Fehler: {}", err_msg));
-        }
-
-        let raw_text = String::from_utf8_lossy(&whisper_output.stdout).to_string();
-        let clean_text = raw_text.trim().to_string();
-
-        println!("\n✨ TRANSCRIPTION (Local):\n{}\n", clean_text);
-
-        // Cleanup temp files (best effort)
-        let _ = std::fs::remove_file(&audio_path);
-        let _ = std::fs::remove_file(&wav_path);
+        engine
+            .transcribe(stt::SttRequest {
+                audio,
+                language: target_lang.clone(),
+                prompt: None,
+            })
+            .await
+    };
 
-        if clean_text.is_empty() {
-            // Maybe it outputted to stderr or something else?
-            // Without -nt usually it prints to stdout.
-            // Let's assume user has a working setup.
-            return Err("Lokale Transkription war leer.".to_string());
+    let transcript = match transcript {
+        Ok(transcript) => {
+            println!(
+                "\n✨ TRANSCRIPTION ({}):\n{}\n",
+                engine.name(),
+                transcript.text
+            );
+            log_confidence(&transcript);
+            transcript
         }
-
-        clean_text
-    } else {
-        // Standard Mode: Groq Whisper
-        println!("☁️ STT (Cloud): Groq Whisper (whisper-large-v3)");
-        match llm::whisper::upload_audio(audio_data, target_lang_cloud.clone()).await {
-            Ok(text) => {
-                println!("\n✨ TRANSCRIPTION (Cloud):\n{}\n", text);
-                text
-            }
-            Err(e) => {
-                eprintln!("❌ Whisper API error: {}", e);
-                return Err(e);
-            }
+        Err(e) => {
+            eprintln!("❌ STT error: {}", e);
+            return Err(e);
         }
     };
 
     // === PHASE 9: OUTPUT FILTER (Hallucination Check) ===
-    if is_hallucination(&transcription) {
-        println!(
-            "🧠 Hallucination detected ('{}'). Filtering output.",
-            transcription.trim()
-        );
-        // Cleanup temp file if it exists
-        let _ = std::fs::remove_file(&audio_path);
-        let _ = std::fs::remove_file(&wav_path);
-        return Ok("".to_string());
-    }
+    let Some(stt::Transcript {
+        text: transcription,
+        language,
+        ..
+    }) = spoken_text(transcript, clip_ms, &hallucination_config)
+    else {
+        return Ok(Response::default());
+    };
 
     // Step 2: Build system prompt from loaded skills
 
-    let system_prompt =
-        llm::prompt::build_system_prompt(target_lang_cloud.clone(), Some(skill.clone()));
+    let system_prompt = llm::prompt::build_system_prompt(target_lang.clone(), Some(skill.clone()));
 
     // Step 3: Configure Backend (Groq vs Ollama)
     let (base_url, model, api_key, mode_label) = if privacy_mode {
@@ -400,9 +954,40 @@ async fn process_audio(
         }
     };
 
+    Ok(Response {
+        text: response_text,
+        language,
+    })
+}
+
+/// The transcript left for the skill after the hallucination filter, `None` = no speech.
+/// An empty transcript means the same for every engine (local, cloud or chunked): the VAD
+/// heard something, but it was no speech.
+fn spoken_text(
+    transcript: stt::Transcript,
+    clip_ms: Option<u64>,
+    config: &stt::hallucination::HallucinationConfig,
+) -> Option<stt::Transcript> {
+    if transcript.text.trim().is_empty() {
+        println!("🔇 Empty transcript. Nothing to process.");
+        return None;
+    }
+
+    let outcome = stt::hallucination::filter(transcript, clip_ms, config);
+    for removal in &outcome.removed {
+        println!(
+            "🧠 Hallucination filtered [{}] '{}' ({})",
+            removal.rule, removal.text, removal.detail
+        );
+    }
+    Some(outcome.transcript).filter(|transcript| !transcript.text.trim().is_empty())
+}
+
+/// Copy the response to the clipboard, hand focus back and type it at the cursor
+fn deliver_response(app_handle: &tauri::AppHandle, response_text: &str) {
     // Step 5: Copy response to clipboard (backup)
 
-    if let Err(e) = input::injector::copy_to_clipboard(&response_text) {
+    if let Err(e) = input::injector::copy_to_clipboard(response_text) {
         eprintln!("⚠ Clipboard error: {}", e);
     }
 
@@ -417,15 +1002,10 @@ async fn process_audio(
 
     // Step 8: Type the response text at cursor position
 
-    if let Err(e) = input::injector::type_text(&response_text) {
+    if let Err(e) = input::injector::type_text(response_text) {
         eprintln!("❌ Injection error: {}", e);
         // Response is still in clipboard as backup
     }
-
-    // Step 9: Emit completion event to frontend
-    let _ = app_handle.emit("processing-complete", ());
-
-    Ok(response_text)
 }
 
 /// Test if local configuration is valid (binary runs, model exists)
@@ -488,6 +1068,27 @@ async fn set_window_expand(expand: bool, window: tauri::WebviewWindow) -> Result
     Ok(())
 }
 
+/// Return the persisted backend configuration
+#[tauri::command]
+fn get_config(state: tauri::State<'_, Mutex<AppState>>) -> Result<AppConfig, String> {
+    state
+        .lock()
+        .map(|s| s.config.clone())
+        .map_err(|e| e.to_string())
+}
+
+/// Replace and persist the backend configuration
+#[tauri::command]
+fn update_config(
+    config: AppConfig,
+    app: tauri::AppHandle,
+    state: tauri::State<'_, Mutex<AppState>>,
+) -> Result<(), String> {
+    config::save(&app, &config)?;
+    state.lock().map_err(|e| e.to_string())?.config = config;
+    Ok(())
+}
+
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
     tauri::Builder::default()
@@ -495,22 +1096,30 @@ pub fn run() {
         .plugin(
             tauri_plugin_global_shortcut::Builder::new()
                 .with_handler(|app, shortcut, event| {
-                    // Only handle key press, not release
-                    if event.state() == ShortcutState::Pressed {
-                        // Alt+Space hotkey
-                        let alt_space = Shortcut::new(Some(Modifiers::ALT), Code::Space);
-                        if shortcut == &alt_space {
-                            toggle_window(app);
-                        }
+                    // Alt+Space hotkey (press and release, for push-to-talk)
+                    let alt_space = Shortcut::new(Some(Modifiers::ALT), Code::Space);
+                    if shortcut == &alt_space {
+                        handle_hotkey(app, event.state());
                     }
                 })
                 .build(),
         )
         .invoke_handler(tauri::generate_handler![
+            start_recording,
+            stop_recording,
+            cancel_recording,
+            start_session,
+            stop_session,
+            list_input_devices,
+            sync_wake_listener,
+            sync_folder_watcher,
             process_audio,
+            transcribe_file,
             set_window_expand,
             test_local_configuration,
-            hide_window
+            hide_window,
+            get_config,
+            update_config
         ])
         // Smart Z-Index: Re-assert always_on_top on ALL Focus events
         // When we gain focus: ensure we're on top
@@ -559,8 +1168,9 @@ pub fn run() {
             let quit = MenuItem::with_id(app, "quit", "Quit", true, None::<&str>)?;
             let menu = Menu::with_items(app, &[&show_hide, &quit])?;
 
-            let _tray = TrayIconBuilder::new()
+            let _tray = TrayIconBuilder::with_id(TRAY_ID)
                 .icon(app.default_window_icon().unwrap().clone())
+                .tooltip("Voice Intelligence")
                 .menu(&menu)
                 .on_menu_event(|app, event| match event.id.as_ref() {
                     "show_hide" => toggle_window(app),
@@ -579,13 +1189,57 @@ pub fn run() {
                 })
                 .build(app)?;
 
-            // Store shared state (currently empty, but keeping pattern)
+            // Store shared state (backend config is loaded from disk once)
             app.manage(Mutex::new(AppState {
-                is_recording: false,
+                recording: None,
+                session: None,
+                hotkey_pressed_at: None,
+                wake: None,
+                watcher: None,
+                engines: stt::SharedEngines::default(),
+                config: config::load(app.handle()),
             }));
 
             Ok(())
         })
-        .run(tauri::generate_context!())
-        .expect("error while running tauri application");
+        .build(tauri::generate_context!())
+        .expect("error while building tauri application")
+        .run(|app, event| {
+            // Don't leave a whisper-server running after quit
+            if let tauri::RunEvent::Exit = event {
+                if let Ok(state) = app.state::<Mutex<AppState>>().lock() {
+                    state.engines.shutdown();
+                }
+            }
+        });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn text_only(text: &str) -> stt::Transcript {
+        stt::Transcript {
+            text: text.to_string(),
+            ..stt::Transcript::default()
+        }
+    }
+
+    #[test]
+    fn empty_transcripts_are_no_speech() {
+        let config = stt::hallucination::HallucinationConfig::default();
+        for text in ["", "  "] {
+            assert!(spoken_text(text_only(text), Some(5_000), &config).is_none());
+            assert!(spoken_text(text_only(text), None, &config).is_none());
+        }
+    }
+
+    #[test]
+    fn filtered_transcripts_are_no_speech_and_the_rest_is_kept() {
+        let config = stt::hallucination::HallucinationConfig::default();
+        assert!(spoken_text(text_only("Vielen Dank."), Some(1_500), &config).is_none());
+
+        let kept = spoken_text(text_only("Milch kaufen"), Some(1_500), &config).unwrap();
+        assert_eq!(kept.text, "Milch kaufen");
+    }
 }
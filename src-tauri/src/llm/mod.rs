@@ -1,3 +1,2 @@
 pub mod groq;
 pub mod prompt;
-pub mod whisper;
@@ -0,0 +1,29 @@
+//! Child Process Piping
+//!
+//! Runs external tools with their input fed through stdin, so audio never needs a file.
+
+use std::io::Write;
+use std::process::{Command, Output, Stdio};
+
+/// Run `command` with `input` written to its stdin and collect stdout/stderr
+pub fn output_with_stdin(command: &mut Command, input: Vec<u8>) -> std::io::Result<Output> {
+    let mut child = command
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()?;
+
+    let mut stdin = child
+        .stdin
+        .take()
+        .ok_or_else(|| std::io::Error::other("stdin nicht verfügbar"))?;
+
+    // Write from a separate thread: the child may fill its stdout pipe before reading all input
+    let writer = std::thread::spawn(move || stdin.write_all(&input));
+    let output = child.wait_with_output()?;
+
+    // A child that exits early closes stdin (broken pipe); its exit status reports the real error
+    let _ = writer.join();
+
+    Ok(output)
+}
@@ -0,0 +1,194 @@
+//! Continuous Dictation
+//!
+//! Keeps the microphone open for a whole writing session and cuts the stream into
+//! utterances at speech pauses. Utterances are processed in parallel, but an injector
+//! awaits them strictly in the order spoken, so a short late sentence can never be typed
+//! before a long earlier one.
+
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+
+use serde::Serialize;
+use tauri::async_runtime::JoinHandle;
+use tauri::{Emitter, Manager};
+use tokio::sync::{mpsc, oneshot};
+
+use crate::audio::endpoint::{EndpointConfig, Endpointer};
+use crate::audio::level::{AudioLevel, LevelMeter};
+use crate::audio::vad::VadConfig;
+use crate::audio::{capture::Recording, AudioBuffer};
+use crate::{AppState, PipelineSettings};
+
+/// Cut an utterance even without a pause (Whisper processes 30 s windows)
+const MAX_UTTERANCE_MS: u32 = 30_000;
+
+/// Session progress for the HUD (`session-state` event)
+#[derive(Clone, Serialize)]
+struct SessionState {
+    active: bool,
+    /// Utterances recorded but not yet typed
+    pending: usize,
+}
+
+/// Handle to a running session, kept in `AppState`
+pub struct Session {
+    /// Hands the audio after the last pause to the driver when the session ends
+    final_tx: oneshot::Sender<AudioBuffer>,
+    driver: JoinHandle<()>,
+}
+
+/// Signals every pause in a session's capture
+pub type Pauses = mpsc::UnboundedReceiver<()>;
+
+impl Session {
+    /// Open the microphone for a session. Blocks while the device opens, so call it off
+    /// the async runtime and without holding the state lock.
+    pub fn open(
+        app: &tauri::AppHandle,
+        device: Option<String>,
+        vad_config: VadConfig,
+        silence_ms: u32,
+    ) -> Result<(Recording, Pauses), String> {
+        // The capture thread signals every pause; the driver then takes the audio so far
+        let (pause_tx, pause_rx) = mpsc::unbounded_channel();
+        let mut splitter = Endpointer::new(EndpointConfig {
+            auto_stop: true,
+            silence_ms,
+            max_duration_ms: MAX_UTTERANCE_MS,
+        });
+        let mut report_level = crate::level_reporter(app.clone());
+
+        let on_level = move |level: AudioLevel| {
+            report_level(level);
+            if splitter.push(&level).is_some() {
+                splitter.reset();
+                let _ = pause_tx.send(());
+            }
+        };
+
+        let recording = Recording::start(device, LevelMeter::new(vad_config), on_level)?;
+        Ok((recording, pause_rx))
+    }
+
+    /// Process utterances in the background; the recording from `open` must already be
+    /// stored in `state.recording`
+    pub fn start(app: &tauri::AppHandle, settings: PipelineSettings, pauses: Pauses) -> Self {
+        let (final_tx, final_rx) = oneshot::channel();
+        let driver = tauri::async_runtime::spawn(drive(app.clone(), settings, pauses, final_rx));
+
+        println!("🔁 Continuous dictation started");
+        Self { final_tx, driver }
+    }
+
+    /// End the session with the audio since the last pause (capture must already be
+    /// stopped) and wait until every utterance is typed
+    pub async fn finish(self, rest: AudioBuffer) {
+        let _ = self.final_tx.send(rest);
+        if let Err(e) = self.driver.await {
+            eprintln!("❌ Session error: {}", e);
+        }
+        println!("🔁 Continuous dictation ended");
+    }
+}
+
+/// Cut utterances at each pause and queue them for processing in spoken order
+async fn drive(
+    app: tauri::AppHandle,
+    settings: PipelineSettings,
+    mut pause_rx: Pauses,
+    final_rx: oneshot::Receiver<AudioBuffer>,
+) {
+    let pending = Arc::new(AtomicUsize::new(0));
+    let (queue_tx, queue_rx) = mpsc::unbounded_channel();
+    let injector =
+        tauri::async_runtime::spawn(inject_in_order(app.clone(), queue_rx, pending.clone()));
+    emit_state(&app, true, 0);
+
+    let enqueue = |buffer: AudioBuffer| {
+        let count = pending.fetch_add(1, Ordering::SeqCst) + 1;
+        emit_state(&app, true, count);
+        let job =
+            tauri::async_runtime::spawn(process_utterance(app.clone(), settings.clone(), buffer));
+        let _ = queue_tx.send(job);
+    };
+
+    // Closes when the capture thread ends
+    while pause_rx.recv().await.is_some() {
+        let buffer = app
+            .state::<Mutex<AppState>>()
+            .lock()
+            .ok()
+            .and_then(|state| state.recording.as_ref().map(Recording::take_buffer));
+        if let Some(buffer) = buffer {
+            enqueue(buffer);
+        }
+    }
+
+    if let Ok(rest) = final_rx.await {
+        enqueue(rest);
+    }
+
+    drop(queue_tx);
+    let _ = injector.await;
+    emit_state(&app, false, 0);
+}
+
+/// STT and skill for one utterance
+async fn process_utterance(
+    app: tauri::AppHandle,
+    settings: PipelineSettings,
+    buffer: AudioBuffer,
+) -> Result<String, String> {
+    if buffer.samples.is_empty() {
+        return Ok(String::new());
+    }
+    crate::transcribe_and_respond(Ok(buffer), None, &settings, &app)
+        .await
+        .map(|response| response.text)
+}
+
+/// Type results strictly in queue order, whenever each one is ready
+async fn inject_in_order(
+    app: tauri::AppHandle,
+    mut queue_rx: mpsc::UnboundedReceiver<JoinHandle<Result<String, String>>>,
+    pending: Arc<AtomicUsize>,
+) {
+    while let Some(job) = queue_rx.recv().await {
+        match job.await {
+            Ok(Ok(text)) if !text.is_empty() => inject(&app, &text),
+            Ok(Ok(_)) => {}
+            Ok(Err(e)) => {
+                eprintln!("❌ Utterance failed: {}", e);
+                let _ = app.emit("session-error", e);
+            }
+            Err(e) => eprintln!("❌ Utterance task failed: {}", e),
+        }
+
+        let count = pending.fetch_sub(1, Ordering::SeqCst) - 1;
+        emit_state(&app, true, count);
+    }
+}
+
+/// Type one utterance, separated from the next by a space
+fn inject(app: &tauri::AppHandle, text: &str) {
+    let text = format!("{} ", text.trim());
+    let window = app.get_webview_window("main");
+    let hud_focused = window
+        .as_ref()
+        .and_then(|window| window.is_focused().ok())
+        .unwrap_or(false);
+
+    if hud_focused {
+        // The HUD still has focus (right after the hotkey): hand it back, keep the HUD visible
+        crate::deliver_response(app, &text);
+        if let Some(window) = &window {
+            crate::show_window_inactive(window);
+        }
+    } else if let Err(e) = crate::input::injector::type_text(&text) {
+        eprintln!("❌ Injection error: {}", e);
+    }
+}
+
+fn emit_state(app: &tauri::AppHandle, active: bool, pending: usize) {
+    let _ = app.emit("session-state", SessionState { active, pending });
+}
@@ -0,0 +1,205 @@
+//! Chunked Transcription
+//!
+//! Transcribes long recordings piece by piece: chunks stay below provider upload limits,
+//! run in parallel up to a configurable concurrency, and are stitched back together in
+//! recording order. Works with any [`SpeechToText`] engine; progress is reported through a
+//! callback (the HUD's `transcription-progress` event).
+
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+
+use serde::Serialize;
+use tokio::sync::Semaphore;
+
+use super::{AudioInput, SpeechToText, SttRequest, Transcript};
+use crate::audio::chunk::Chunk;
+use crate::audio::AudioBuffer;
+
+/// Words compared when removing the repeated overlap between two chunks
+const MAX_OVERLAP_WORDS: usize = 12;
+
+/// Leading words of a chunk that may be garbled (cut mid-word) before the repeat starts
+const MAX_OVERLAP_SKEW: usize = 2;
+
+/// Progress for the HUD (`transcription-progress` event)
+#[derive(Debug, Clone, Copy, Serialize)]
+pub struct Progress {
+    pub done: usize,
+    pub total: usize,
+}
+
+/// Transcribe all chunks with at most `concurrency` in flight and join the results in order.
+/// Segment times are shifted to the position of their chunk in the recording.
+pub async fn transcribe(
+    engine: Arc<dyn SpeechToText>,
+    buffer: &AudioBuffer,
+    chunks: &[Chunk],
+    language: Option<String>,
+    concurrency: usize,
+    on_progress: impl Fn(Progress) + Send + Sync + 'static,
+) -> Result<Transcript, String> {
+    let total = chunks.len();
+    let on_progress = Arc::new(on_progress);
+    let permits = Arc::new(Semaphore::new(concurrency.max(1)));
+    let done = Arc::new(AtomicUsize::new(0));
+    on_progress(Progress { done: 0, total });
+
+    let jobs: Vec<_> = chunks
+        .iter()
+        .map(|chunk| {
+            let request = SttRequest {
+                audio: AudioInput::Pcm(buffer.slice(chunk.range.clone())),
+                language: language.clone(),
+                prompt: None,
+            };
+            let (engine, permits, done, on_progress) = (
+                engine.clone(),
+                permits.clone(),
+                done.clone(),
+                on_progress.clone(),
+            );
+            tauri::async_runtime::spawn(async move {
+                let _permit = permits.acquire_owned().await.map_err(|e| e.to_string())?;
+                let transcript = engine.transcribe(request).await?;
+                let done = done.fetch_add(1, Ordering::SeqCst) + 1;
+                on_progress(Progress { done, total });
+                Ok::<_, String>(transcript)
+            })
+        })
+        .collect();
+
+    let to_ms = |sample: usize| sample as u64 * 1000 / buffer.sample_rate as u64;
+    let mut result = Transcript::default();
+    let mut jobs = jobs.into_iter();
+    for (index, chunk) in chunks.iter().enumerate() {
+        let Some(job) = jobs.next() else { break };
+        let part = match job.await.unwrap_or_else(|e| Err(e.to_string())) {
+            Ok(part) => part,
+            Err(e) => {
+                // Don't keep transcribing chunks of a transcript that is already lost
+                jobs.for_each(|job| job.abort());
+                return Err(format!("Chunk {}/{}: {}", index + 1, total, e));
+            }
+        };
+        println!(
+            "🧩 Chunk {}/{}: {} chars",
+            index + 1,
+            total,
+            part.text.len()
+        );
+
+        append(&mut result.text, part.text.trim(), chunk.overlaps_previous);
+        result.language = result.language.or(part.language);
+
+        let offset = to_ms(chunk.range.start);
+        // Segments inside the repeated overlap were already covered by the previous chunk
+        let covered_until = result.segments.last().map_or(0, |s| s.end_ms);
+        result.segments.extend(
+            part.segments
+                .into_iter()
+                .map(|mut segment| {
+                    segment.start_ms += offset;
+                    segment.end_ms += offset;
+                    segment
+                })
+                .filter(|segment| !chunk.overlaps_previous || segment.end_ms > covered_until),
+        );
+    }
+    Ok(result)
+}
+
+/// Append a chunk's text, dropping words the previous chunk already ended with
+fn append(transcript: &mut String, text: &str, overlaps_previous: bool) {
+    let mut words: Vec<&str> = text.split_whitespace().collect();
+    if overlaps_previous {
+        let skip = repeated_prefix(transcript, &words);
+        words.drain(..skip);
+    }
+    if words.is_empty() {
+        return;
+    }
+    if !transcript.is_empty() {
+        transcript.push(' ');
+    }
+    transcript.push_str(&words.join(" "));
+}
+
+/// Number of leading words of `next` that repeat the end of `previous` (0 if none).
+/// At least two matching words are required so a genuine repetition is kept.
+fn repeated_prefix(previous: &str, next: &[&str]) -> usize {
+    let previous: Vec<&str> = previous.split_whitespace().collect();
+    let tail: Vec<String> = previous[previous.len().saturating_sub(MAX_OVERLAP_WORDS)..]
+        .iter()
+        .map(|word| normalize(word))
+        .collect();
+    let head: Vec<String> = next
+        .iter()
+        .take(MAX_OVERLAP_WORDS + MAX_OVERLAP_SKEW)
+        .map(|word| normalize(word))
+        .collect();
+
+    for len in (2..=tail.len().min(head.len())).rev() {
+        for skew in 0..=MAX_OVERLAP_SKEW.min(head.len() - len) {
+            if head[skew..skew + len] == tail[tail.len() - len..] {
+                return skew + len;
+            }
+        }
+    }
+    0
+}
+
+/// Lowercase word without punctuation, for comparing chunk seams
+fn normalize(word: &str) -> String {
+    word.chars()
+        .filter(|c| c.is_alphanumeric())
+        .flat_map(char::to_lowercase)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn words(text: &str) -> Vec<&str> {
+        text.split_whitespace().collect()
+    }
+
+    #[test]
+    fn exact_overlap_is_removed() {
+        let previous = "Wir treffen uns am Montag um zehn";
+        let next = words("Montag, um zehn Uhr im Büro.");
+        assert_eq!(repeated_prefix(previous, &next), 3);
+
+        let mut transcript = previous.to_string();
+        append(&mut transcript, "Montag, um zehn Uhr im Büro.", true);
+        assert_eq!(transcript, "Wir treffen uns am Montag um zehn Uhr im Büro.");
+    }
+
+    #[test]
+    fn garbled_words_at_the_seam_are_skipped() {
+        // The hard cut split "Montag"; the fragment comes before the repeat
+        let previous = "Wir treffen uns am Montag um zehn";
+        let next = words("tag um zehn Uhr im Büro.");
+        assert_eq!(repeated_prefix(previous, &next), 3);
+
+        let next = words("ag, äh um zehn Uhr");
+        assert_eq!(repeated_prefix(previous, &next), 4);
+    }
+
+    #[test]
+    fn unrelated_or_single_word_starts_are_kept() {
+        let previous = "Wir treffen uns am Montag um zehn";
+        assert_eq!(
+            repeated_prefix(previous, &words("Danach gehen wir essen.")),
+            0
+        );
+        // One matching word could be genuine repetition ("zehn, zehn Minuten später")
+        assert_eq!(repeated_prefix(previous, &words("zehn Minuten später.")), 0);
+        assert_eq!(repeated_prefix("", &words("Hallo Welt")), 0);
+
+        // Pause cuts never drop words, even if they look like a repeat
+        let mut transcript = previous.to_string();
+        append(&mut transcript, "um zehn Uhr.", false);
+        assert_eq!(transcript, "Wir treffen uns am Montag um zehn um zehn Uhr.");
+    }
+}
@@ -0,0 +1,290 @@
+//! Command-Template Engine
+//!
+//! Runs any local STT tool (faster-whisper, whisper-ctranslate2, vosk scripts, ...) from a
+//! command template in the config. Placeholders are filled per request, and the result is
+//! read from stdout, a JSON file or an SRT file, as declared in `stt.command.output`.
+
+use std::path::Path;
+use std::process::Command;
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+
+use super::openai::WhisperResponse;
+use super::{
+    join_segments, parse_timestamp, whisper_cpp, EngineContext, Segment, SpeechToText, SttRequest,
+    Transcript,
+};
+use crate::audio::{self, AudioBuffer};
+use crate::pipe;
+use crate::workspace::Workspace;
+
+/// Where the tool puts its transcript
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CommandOutput {
+    /// Plain text on stdout
+    #[default]
+    Stdout,
+    /// `{output_file}` with `text` and/or `segments` (`start`/`end` in seconds), OpenAI style
+    Json,
+    /// `{output_file}` in SubRip format
+    Srt,
+}
+
+/// Command-template settings (persisted in the backend config)
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CommandConfig {
+    /// Program followed by its arguments; each entry may contain `{input_wav}`, `{model}`,
+    /// `{language}` and `{output_file}`. No shell is involved, so no quoting is needed.
+    pub template: Vec<String>,
+    pub output: CommandOutput,
+    /// Value for `{model}`, empty = the model path from the settings
+    pub model: String,
+    /// Value for `{language}` when no language is set (e.g. `auto` for whisper.cpp tools)
+    pub auto_language: String,
+}
+
+#[derive(Clone)]
+pub struct CommandEngine {
+    config: CommandConfig,
+    model: String,
+    in_memory_audio: bool,
+}
+
+impl CommandEngine {
+    /// Replace the placeholders in one template entry
+    fn fill(
+        &self,
+        arg: &str,
+        input_wav: &str,
+        language: Option<&str>,
+        output_file: &str,
+    ) -> String {
+        arg.replace("{input_wav}", input_wav)
+            .replace("{model}", &self.model)
+            .replace("{language}", language.unwrap_or(&self.config.auto_language))
+            .replace("{output_file}", output_file)
+    }
+
+    /// Fill the template and run the tool (blocking)
+    fn run(&self, buffer: &AudioBuffer, language: Option<&str>) -> Result<Transcript, String> {
+        let workspace = Workspace::new(self.in_memory_audio)?;
+        let wav_data = audio::wav::encode(buffer);
+
+        // In strict in-memory mode the WAV is piped and `{input_wav}` becomes "-" (stdin)
+        let input_wav = if workspace.is_in_memory() {
+            "-".to_string()
+        } else {
+            path_arg(&workspace.write("input.wav", &wav_data)?)
+        };
+        let output_file = match self.config.output {
+            CommandOutput::Stdout => None,
+            CommandOutput::Json => Some(workspace.path("output.json")?),
+            CommandOutput::Srt => Some(workspace.path("output.srt")?),
+        };
+
+        let output_arg = output_file.as_deref().map(path_arg).unwrap_or_default();
+        let fill = |arg: &str| self.fill(arg, &input_wav, language, &output_arg);
+        let (program, args) = self
+            .config
+            .template
+            .split_first()
+            .ok_or("Kein STT-Befehl konfiguriert")?;
+        let mut command = Command::new(fill(program));
+        command.args(args.iter().map(|arg| fill(arg)));
+
+        let output = if workspace.is_in_memory() {
+            pipe::output_with_stdin(&mut command, wav_data)
+        } else {
+            command.output()
+        }
+        .map_err(|e| format!("Fehler beim Ausführen von {}: {}", program, e))?;
+
+        if !output.status.success() {
+            let err_msg = String::from_utf8_lossy(&output.stderr);
+            return Err(format!("STT-Befehl fehlgeschlagen: {}", err_msg));
+        }
+
+        let Some(output_file) = output_file else {
+            let text = String::from_utf8_lossy(&output.stdout);
+            return Ok(Transcript {
+                text: text.trim().to_string(),
+                ..Transcript::default()
+            });
+        };
+        let content = std::fs::read_to_string(&output_file)
+            .map_err(|e| format!("Ausgabedatei {} nicht lesbar: {}", output_file.display(), e))?;
+        match self.config.output {
+            CommandOutput::Json => serde_json::from_str::<WhisperResponse>(&content)
+                .map(Transcript::from)
+                .map_err(|e| format!("Fehler beim Parsen der Ausgabe: {}", e)),
+            _ => {
+                let segments = parse_srt(&content);
+                Ok(Transcript {
+                    text: join_segments(&segments),
+                    language: None,
+                    segments,
+                })
+            }
+        }
+    }
+}
+
+#[async_trait]
+impl SpeechToText for CommandEngine {
+    fn name(&self) -> String {
+        let program = self
+            .config
+            .template
+            .first()
+            .and_then(|program| Path::new(program).file_stem())
+            .map(|name| name.to_string_lossy().into_owned())
+            .unwrap_or_default();
+        format!("command ({})", program)
+    }
+
+    fn is_local(&self) -> bool {
+        true
+    }
+
+    async fn transcribe(&self, request: SttRequest) -> Result<Transcript, String> {
+        let engine = self.clone();
+        tauri::async_runtime::spawn_blocking(move || {
+            let buffer = request.audio.into_pcm()?;
+            // Tools built on Whisper emit the same non-speech tokens as whisper.cpp
+            engine
+                .run(&buffer, request.language.as_deref())
+                .map(whisper_cpp::clean)
+        })
+        .await
+        .map_err(|e| e.to_string())?
+    }
+}
+
+/// Registry factory: needs a template in `stt.command`
+pub fn create(context: &EngineContext) -> Result<Arc<dyn SpeechToText>, String> {
+    let config = context.config.command.clone();
+    if config.template.is_empty() {
+        return Err("Kein STT-Befehl konfiguriert (stt.command.template)".to_string());
+    }
+    if context.in_memory_audio && config.output != CommandOutput::Stdout {
+        return Err(
+            "Strikter RAM-Modus: STT-Befehl muss das Ergebnis auf stdout ausgeben".to_string(),
+        );
+    }
+    let model = if config.model.is_empty() {
+        context.model_path.clone()
+    } else {
+        config.model.clone()
+    };
+    Ok(Arc::new(CommandEngine {
+        config,
+        model,
+        in_memory_audio: context.in_memory_audio,
+    }))
+}
+
+fn path_arg(path: &Path) -> String {
+    path.to_string_lossy().into_owned()
+}
+
+/// Segments of a SubRip file; malformed blocks are skipped
+fn parse_srt(content: &str) -> Vec<Segment> {
+    let content = content.replace("\r\n", "\n");
+    content
+        .split("\n\n")
+        .filter_map(|block| {
+            let mut lines = block.lines().map(str::trim).filter(|l| !l.is_empty());
+            // The cue number is optional in practice, the timing line is not
+            let mut timing = lines.next()?;
+            if !timing.contains("-->") {
+                timing = lines.next()?;
+            }
+            let (start, end) = timing.split_once("-->")?;
+            Some(Segment::new(
+                parse_timestamp(start)?,
+                parse_timestamp(end)?,
+                lines.collect::<Vec<_>>().join(" "),
+            ))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn engine(template: &[&str], auto_language: &str) -> CommandEngine {
+        CommandEngine {
+            config: CommandConfig {
+                template: template.iter().map(|arg| arg.to_string()).collect(),
+                auto_language: auto_language.to_string(),
+                ..CommandConfig::default()
+            },
+            model: "base.bin".to_string(),
+            in_memory_audio: false,
+        }
+    }
+
+    #[test]
+    fn language_placeholder_uses_the_configured_auto_value() {
+        let fill = |engine: &CommandEngine, language| {
+            engine.fill("--lang={language}", "in.wav", language, "")
+        };
+        assert_eq!(fill(&engine(&[], ""), None), "--lang=");
+        assert_eq!(fill(&engine(&[], "auto"), None), "--lang=auto");
+        assert_eq!(fill(&engine(&[], "auto"), Some("de")), "--lang=de");
+        assert_eq!(
+            engine(&[], "").fill(
+                "{model} {input_wav} {output_file}",
+                "in.wav",
+                None,
+                "out.srt"
+            ),
+            "base.bin in.wav out.srt"
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn non_speech_tokens_are_removed() {
+        for (output, expected) in [("[BLANK_AUDIO] Hallo Welt", "Hallo Welt"), ("(music)", "")] {
+            let script = format!("echo '{}'", output);
+            let engine = engine(&["sh", "-c", &script], "");
+            let request = SttRequest {
+                audio: super::super::AudioInput::Pcm(AudioBuffer::new(vec![0; 1_600])),
+                language: None,
+                prompt: None,
+            };
+            let transcript = tauri::async_runtime::block_on(engine.transcribe(request)).unwrap();
+            assert_eq!(transcript.text, expected);
+        }
+    }
+
+    #[test]
+    fn parses_srt_cues() {
+        let srt = "1\r\n00:00:00,000 --> 00:00:02,500\r\nHallo zusammen,\r\n\r\n\
+                   2\r\n00:00:02,500 --> 00:01:03.040\r\nerste Zeile\r\nzweite Zeile\r\n\r\n";
+        let segments = parse_srt(srt);
+
+        assert_eq!(segments.len(), 2);
+        assert_eq!((segments[0].start_ms, segments[0].end_ms), (0, 2_500));
+        assert_eq!(segments[0].text, "Hallo zusammen,");
+        assert_eq!((segments[1].start_ms, segments[1].end_ms), (2_500, 63_040));
+        assert_eq!(segments[1].text, "erste Zeile zweite Zeile");
+        assert_eq!(
+            join_segments(&segments),
+            "Hallo zusammen, erste Zeile zweite Zeile"
+        );
+    }
+
+    #[test]
+    fn skips_malformed_srt_blocks() {
+        let segments = parse_srt("garbage\n\n3\n00:00:01,000 --> 00:00:02,000\nok\n");
+        assert_eq!(segments.len(), 1);
+        assert_eq!(segments[0].text, "ok");
+    }
+}
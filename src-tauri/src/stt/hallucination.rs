@@ -0,0 +1,347 @@
+//! Hallucination Filter
+//!
+//! Whisper invents text for silence and noise ("Vielen Dank.", subtitle credits) and can get
+//! stuck repeating a phrase. Segments are scored with the confidence values the engine
+//! reported; a phrase list catches known artifacts, but only when the phrase is the whole
+//! transcript of a very short clip. Every removal names the rule that fired, so it can be
+//! logged and reviewed.
+
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+
+use super::{compression_ratio, join_segments, Segment, Transcript};
+
+/// Whisper's decoding window; longer segment-less text compresses well without looping
+const WINDOW_MS: u64 = 30_000;
+
+/// Hallucination filter settings (persisted in the backend config)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct HallucinationConfig {
+    pub enabled: bool,
+    /// A segment is silence above this `no_speech_prob`...
+    pub no_speech_prob: f32,
+    /// ...if its `avg_logprob` is also below this (Whisper's own rule)
+    pub logprob_threshold: f32,
+    /// Repetition loop above this compression ratio
+    pub compression_ratio: f32,
+    /// Known artifacts, dropped when one makes up the whole transcript of a short clip
+    /// (never when the clip length is unknown). Case, punctuation and spacing are ignored.
+    pub phrases: Vec<String>,
+    /// Longest clip the phrase list applies to
+    pub phrase_max_clip_ms: u32,
+}
+
+impl Default for HallucinationConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            no_speech_prob: 0.6,
+            logprob_threshold: -1.0,
+            compression_ratio: 2.4,
+            phrases: [
+                "Thank you.",
+                "Thank you for watching.",
+                "Thanks for watching!",
+                "Thanks.",
+                "Subtitles by the Amara.org community",
+                "MBC",
+                "Untertitel der Amara.org-Community",
+                "Untertitel im Auftrag des ZDF",
+                "Sous-titres réalisés par la communauté d'Amara.org",
+                "Lädt...",
+                "Vielen Dank.",
+                "Vielen Dank für Ihre Aufmerksamkeit.",
+                "Danke fürs Zuschauen.",
+            ]
+            .map(String::from)
+            .to_vec(),
+            phrase_max_clip_ms: 3_000,
+        }
+    }
+}
+
+/// Why text was removed
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Rule {
+    /// A single character is all that is left
+    Empty,
+    /// High `no_speech_prob` with low `avg_logprob`
+    NoSpeech,
+    /// Compression ratio of a repetition loop
+    Repetition,
+    /// Phrase list match on a short clip
+    Phrase,
+}
+
+impl fmt::Display for Rule {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Rule::Empty => "empty",
+            Rule::NoSpeech => "no_speech",
+            Rule::Repetition => "repetition",
+            Rule::Phrase => "phrase",
+        })
+    }
+}
+
+/// Text the filter removed, with the values that decided it
+#[derive(Debug, Clone)]
+pub struct Removal {
+    pub rule: Rule,
+    pub text: String,
+    pub detail: String,
+}
+
+/// Filtered transcript plus what was taken out of it
+#[derive(Debug)]
+pub struct Outcome {
+    pub transcript: Transcript,
+    pub removed: Vec<Removal>,
+}
+
+/// Drop hallucinated segments, or the whole text if it is a known artifact.
+/// `clip_ms` is the length of the audio sent to STT, if known.
+pub fn filter(
+    transcript: Transcript,
+    clip_ms: Option<u64>,
+    config: &HallucinationConfig,
+) -> Outcome {
+    if !config.enabled {
+        return Outcome {
+            transcript,
+            removed: Vec::new(),
+        };
+    }
+    let clip_ms = clip_ms.or_else(|| transcript.segments.last().map(|s| s.end_ms));
+    let mut removed = Vec::new();
+
+    // Segment rules; text-only engines are judged as one segment if it fits Whisper's window
+    let transcript = if transcript.segments.is_empty() {
+        let whole = Segment::new(0, clip_ms.unwrap_or(0), transcript.text.clone());
+        match judge_segment(&whole, config) {
+            Some(removal) if clip_ms.is_some_and(|ms| ms <= WINDOW_MS) => {
+                removed.push(removal);
+                Transcript {
+                    text: String::new(),
+                    ..transcript
+                }
+            }
+            _ => transcript,
+        }
+    } else {
+        let (kept, dropped): (Vec<_>, Vec<_>) = transcript
+            .segments
+            .iter()
+            .map(|segment| (segment, judge_segment(segment, config)))
+            .partition(|(_, removal)| removal.is_none());
+        if dropped.is_empty() {
+            transcript
+        } else {
+            removed.extend(dropped.into_iter().filter_map(|(_, removal)| removal));
+            let segments: Vec<Segment> = kept.into_iter().map(|(s, _)| s.clone()).collect();
+            Transcript {
+                text: join_segments(&segments),
+                language: transcript.language,
+                segments,
+            }
+        }
+    };
+
+    // Whole-text rules
+    let text = transcript.text.trim();
+    let rule = if text.is_empty() {
+        None
+    } else if text.chars().count() < 2 {
+        Some(Rule::Empty)
+    } else if clip_ms.is_some_and(|ms| ms <= config.phrase_max_clip_ms as u64)
+        && config
+            .phrases
+            .iter()
+            .any(|phrase| normalize(phrase) == normalize(text))
+    {
+        Some(Rule::Phrase)
+    } else {
+        None
+    };
+    let transcript = match rule {
+        Some(rule) => {
+            removed.push(Removal {
+                rule,
+                text: text.to_string(),
+                detail: match clip_ms {
+                    Some(ms) => format!("{}ms clip", ms),
+                    None => "clip length unknown".to_string(),
+                },
+            });
+            Transcript {
+                text: String::new(),
+                language: transcript.language,
+                segments: Vec::new(),
+            }
+        }
+        None => transcript,
+    };
+
+    Outcome {
+        transcript,
+        removed,
+    }
+}
+
+/// Segment-level rules; values the engine did not report never fire
+fn judge_segment(segment: &Segment, config: &HallucinationConfig) -> Option<Removal> {
+    let removal = |rule, detail| {
+        Some(Removal {
+            rule,
+            text: segment.text.clone(),
+            detail,
+        })
+    };
+
+    if let Some(no_speech) = segment.no_speech_prob {
+        let unsure = match segment.avg_logprob {
+            Some(logprob) => logprob < config.logprob_threshold,
+            None => true,
+        };
+        if no_speech > config.no_speech_prob && unsure {
+            let logprob = segment
+                .avg_logprob
+                .map_or("-".to_string(), |v| format!("{:.2}", v));
+            return removal(
+                Rule::NoSpeech,
+                format!("no_speech_prob {:.2}, avg_logprob {}", no_speech, logprob),
+            );
+        }
+    }
+
+    let ratio = segment
+        .compression_ratio
+        .or_else(|| compression_ratio(&segment.text))?;
+    if ratio > config.compression_ratio {
+        return removal(Rule::Repetition, format!("compression_ratio {:.2}", ratio));
+    }
+    None
+}
+
+/// Lowercase letters and digits only, single-spaced
+fn normalize(text: &str) -> String {
+    text.chars()
+        .map(|c| if c.is_alphanumeric() { c } else { ' ' })
+        .collect::<String>()
+        .to_lowercase()
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn segment(text: &str, no_speech: f32, logprob: f32) -> Segment {
+        Segment {
+            no_speech_prob: Some(no_speech),
+            avg_logprob: Some(logprob),
+            ..Segment::new(0, 2_000, text.to_string())
+        }
+    }
+
+    fn transcript(segments: Vec<Segment>) -> Transcript {
+        Transcript {
+            text: join_segments(&segments),
+            language: None,
+            segments,
+        }
+    }
+
+    fn text_only(text: &str) -> Transcript {
+        Transcript {
+            text: text.to_string(),
+            ..Transcript::default()
+        }
+    }
+
+    #[test]
+    fn drops_silent_and_looping_segments_only() {
+        let looped = "Vielen Dank. ".repeat(12);
+        let outcome = filter(
+            transcript(vec![
+                segment("Bitte den Bericht bis Freitag schicken.", 0.05, -0.2),
+                segment("Vielen Dank.", 0.85, -1.4),
+                segment(looped.trim(), 0.1, -0.3),
+            ]),
+            Some(9_000),
+            &HallucinationConfig::default(),
+        );
+
+        assert_eq!(
+            outcome.transcript.text,
+            "Bitte den Bericht bis Freitag schicken."
+        );
+        let rules: Vec<_> = outcome.removed.iter().map(|r| r.rule).collect();
+        assert_eq!(rules, [Rule::NoSpeech, Rule::Repetition]);
+    }
+
+    #[test]
+    fn confident_speech_survives_high_no_speech_prob() {
+        let outcome = filter(
+            transcript(vec![segment("Ja.", 0.7, -0.3)]),
+            Some(1_000),
+            &HallucinationConfig::default(),
+        );
+        assert_eq!(outcome.transcript.text, "Ja.");
+        assert!(outcome.removed.is_empty());
+    }
+
+    #[test]
+    fn phrases_only_apply_to_whole_short_clips() {
+        let config = HallucinationConfig::default();
+
+        let outcome = filter(text_only(" vielen dank "), Some(1_500), &config);
+        assert_eq!(outcome.transcript.text, "");
+        assert_eq!(outcome.removed[0].rule, Rule::Phrase);
+
+        // Mid-sentence, or said in a longer clip: real dictation
+        let sentence = "Vielen Dank. Ich melde mich morgen.";
+        assert_eq!(
+            filter(text_only(sentence), Some(1_500), &config)
+                .transcript
+                .text,
+            sentence
+        );
+        let outcome = filter(text_only("Vielen Dank."), Some(8_000), &config);
+        assert_eq!(outcome.transcript.text, "Vielen Dank.");
+    }
+
+    #[test]
+    fn phrases_need_a_known_clip_length() {
+        let config = HallucinationConfig::default();
+
+        // No audio length and no segment times: could be a long dictation
+        let outcome = filter(text_only("Thank you."), None, &config);
+        assert_eq!(outcome.transcript.text, "Thank you.");
+        assert!(outcome.removed.is_empty());
+
+        // Segment times stand in for the clip length
+        let outcome = filter(
+            transcript(vec![Segment::new(0, 1_200, "Thank you.".to_string())]),
+            None,
+            &config,
+        );
+        assert_eq!(outcome.transcript.text, "");
+        assert_eq!(outcome.removed[0].rule, Rule::Phrase);
+    }
+
+    #[test]
+    fn disabled_filter_keeps_everything() {
+        let config = HallucinationConfig {
+            enabled: false,
+            ..HallucinationConfig::default()
+        };
+        let outcome = filter(text_only("Thank you."), Some(1_000), &config);
+        assert_eq!(outcome.transcript.text, "Thank you.");
+        assert!(outcome.removed.is_empty());
+    }
+}
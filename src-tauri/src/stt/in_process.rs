@@ -0,0 +1,262 @@
+//! Local Whisper (in-process)
+//!
+//! Runs whisper.cpp inside the app through `whisper-rs`: PCM goes straight into the model,
+//! without WAV file or child process. The model is loaded once, shared across requests via
+//! `AppState`, and unloaded after an idle timeout. Only available in builds with the
+//! `whisper-rs` feature.
+
+use serde::{Deserialize, Serialize};
+
+/// In-process engine settings (persisted in the backend config)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct InProcessConfig {
+    /// CPU threads for inference, 0 = automatic
+    pub threads: u16,
+    /// Beam search width, 1 = greedy decoding
+    pub beam_size: u16,
+    /// Unload the model after this long without a request
+    pub idle_unload_secs: u64,
+}
+
+impl Default for InProcessConfig {
+    fn default() -> Self {
+        Self {
+            threads: 0,
+            beam_size: 5,
+            idle_unload_secs: 300,
+        }
+    }
+}
+
+#[cfg(not(feature = "whisper-rs"))]
+pub fn create(
+    _context: &super::EngineContext,
+) -> Result<std::sync::Arc<dyn super::SpeechToText>, String> {
+    Err("In-Process Whisper ist in diesem Build nicht enthalten (Feature 'whisper-rs')".to_string())
+}
+
+#[cfg(feature = "whisper-rs")]
+pub use engine::{create, ModelCache};
+
+#[cfg(feature = "whisper-rs")]
+mod engine {
+    use std::path::Path;
+    use std::sync::{Arc, Mutex};
+    use std::time::{Duration, Instant};
+
+    use async_trait::async_trait;
+    use whisper_rs::{FullParams, SamplingStrategy, WhisperContext, WhisperContextParameters};
+
+    use super::InProcessConfig;
+    use crate::stt::{
+        avg_logprob, whisper_cpp, EngineContext, Segment, SpeechToText, SttRequest, Transcript,
+    };
+
+    /// Upper bound for automatic thread count; more rarely helps whisper.cpp
+    const MAX_AUTO_THREADS: usize = 8;
+
+    struct Loaded {
+        model_path: String,
+        context: Arc<WhisperContext>,
+        last_used: Instant,
+    }
+
+    /// The loaded model, shared by all requests (one per app, kept in `AppState`)
+    #[derive(Default)]
+    pub struct ModelCache {
+        loaded: Mutex<Option<Loaded>>,
+    }
+
+    impl ModelCache {
+        /// The model for `model_path`, loading it (and dropping another one) if needed.
+        /// Loading takes seconds with the lock held, so only call it from a blocking thread.
+        fn get(&self, model_path: &str) -> Result<Arc<WhisperContext>, String> {
+            let mut loaded = self.loaded.lock().map_err(|e| e.to_string())?;
+            if let Some(current) = loaded.as_mut().filter(|l| l.model_path == model_path) {
+                current.last_used = Instant::now();
+                return Ok(current.context.clone());
+            }
+
+            let context =
+                WhisperContext::new_with_params(model_path, WhisperContextParameters::default())
+                    .map_err(|e| format!("Fehler beim Laden des Modells {}: {}", model_path, e))?;
+            let context = Arc::new(context);
+            *loaded = Some(Loaded {
+                model_path: model_path.to_string(),
+                context: context.clone(),
+                last_used: Instant::now(),
+            });
+            Ok(context)
+        }
+
+        /// Drop the model if nothing used it for `idle` and no request holds it.
+        /// Runs on the async runtime, so a model that is just loading is skipped, not awaited.
+        fn unload_if_idle(&self, idle: Duration) {
+            let Ok(mut loaded) = self.loaded.try_lock() else {
+                return;
+            };
+            let idle = loaded.as_ref().is_some_and(|l| {
+                l.last_used.elapsed() >= idle && Arc::strong_count(&l.context) == 1
+            });
+            if idle {
+                *loaded = None;
+            }
+        }
+
+        /// Free the model (app quit)
+        pub fn shutdown(&self) {
+            if let Ok(mut loaded) = self.loaded.lock() {
+                *loaded = None;
+            }
+        }
+    }
+
+    #[derive(Clone)]
+    pub struct InProcessWhisper {
+        cache: Arc<ModelCache>,
+        model_path: String,
+        config: InProcessConfig,
+    }
+
+    impl InProcessWhisper {
+        fn threads(&self) -> i32 {
+            let threads = match self.config.threads {
+                0 => std::thread::available_parallelism()
+                    .map_or(4, |n| n.get())
+                    .min(MAX_AUTO_THREADS),
+                n => n as usize,
+            };
+            threads as i32
+        }
+
+        /// Run the model on 16 kHz PCM (blocking)
+        fn run(
+            &self,
+            context: &WhisperContext,
+            samples: &[f32],
+            language: Option<&str>,
+            prompt: Option<&str>,
+        ) -> Result<Transcript, String> {
+            let strategy = match self.config.beam_size {
+                0 | 1 => SamplingStrategy::Greedy { best_of: 1 },
+                beam_size => SamplingStrategy::BeamSearch {
+                    beam_size: beam_size as i32,
+                    patience: -1.0,
+                },
+            };
+            let mut params = FullParams::new(strategy);
+            params.set_n_threads(self.threads());
+            params.set_language(Some(language.unwrap_or("auto")));
+            if let Some(prompt) = prompt {
+                params.set_initial_prompt(prompt);
+            }
+            params.set_print_progress(false);
+            params.set_print_realtime(false);
+            params.set_print_timestamps(false);
+
+            // Own state per request, so parallel chunks share only the weights
+            let mut state = context
+                .create_state()
+                .map_err(|e| format!("Whisper Fehler: {}", e))?;
+            state
+                .full(params, samples)
+                .map_err(|e| format!("Whisper Fehler: {}", e))?;
+
+            let count = state
+                .full_n_segments()
+                .map_err(|e| format!("Whisper Fehler: {}", e))?;
+            let mut segments = Vec::with_capacity(count.max(0) as usize);
+            for i in 0..count {
+                let text = state
+                    .full_get_segment_text_lossy(i)
+                    .map_err(|e| format!("Whisper Fehler: {}", e))?;
+                // Timestamps are in 10 ms steps
+                let start = state.full_get_segment_t0(i).unwrap_or(0).max(0) as u64 * 10;
+                let end = state.full_get_segment_t1(i).unwrap_or(0).max(0) as u64 * 10;
+                // Tokens from EOT upwards are control and timestamp tokens
+                let tokens = state.full_n_tokens(i).unwrap_or(0);
+                let probabilities = (0..tokens)
+                    .filter_map(|token| state.full_get_token_data(i, token).ok())
+                    .filter(|data| data.id < context.token_eot())
+                    .map(|data| data.p);
+                segments.push(Segment {
+                    avg_logprob: avg_logprob(probabilities),
+                    ..Segment::new(start, end, text.trim().to_string())
+                });
+            }
+
+            let language = state
+                .full_lang_id_from_state()
+                .ok()
+                .and_then(whisper_rs::get_lang_str)
+                .map(str::to_string);
+
+            // Text is joined from the segments once non-speech tokens are gone
+            Ok(whisper_cpp::clean(Transcript {
+                text: String::new(),
+                language,
+                segments,
+            }))
+        }
+    }
+
+    #[async_trait]
+    impl SpeechToText for InProcessWhisper {
+        fn name(&self) -> String {
+            let model = Path::new(&self.model_path)
+                .file_name()
+                .map(|name| name.to_string_lossy().into_owned())
+                .unwrap_or_default();
+            format!("whisper-rs ({})", model)
+        }
+
+        fn is_local(&self) -> bool {
+            true
+        }
+
+        async fn transcribe(&self, request: SttRequest) -> Result<Transcript, String> {
+            let engine = self.clone();
+
+            let result = tauri::async_runtime::spawn_blocking(move || {
+                let context = engine.cache.get(&engine.model_path)?;
+                let buffer = request.audio.into_pcm()?;
+                let samples: Vec<f32> = buffer
+                    .samples
+                    .iter()
+                    .map(|&s| s as f32 / i16::MAX as f32)
+                    .collect();
+                engine.run(
+                    &context,
+                    &samples,
+                    request.language.as_deref(),
+                    request.prompt.as_deref(),
+                )
+            })
+            .await
+            .map_err(|e| e.to_string())?;
+
+            // The spawned context clone is gone now, so an idle check can drop the model
+            let cache = self.cache.clone();
+            let idle = Duration::from_secs(self.config.idle_unload_secs);
+            tauri::async_runtime::spawn(async move {
+                tokio::time::sleep(idle).await;
+                cache.unload_if_idle(idle);
+            });
+
+            result
+        }
+    }
+
+    /// Registry factory: needs a model file from the settings
+    pub fn create(context: &EngineContext) -> Result<Arc<dyn SpeechToText>, String> {
+        if context.model_path.is_empty() {
+            return Err("Settings error: Local Whisper binaries not configured. Please check 'Local Mode' in settings.".to_string());
+        }
+        Ok(Arc::new(InProcessWhisper {
+            cache: context.shared.whisper_models.clone(),
+            model_path: context.model_path.clone(),
+            config: context.config.in_process.clone(),
+        }))
+    }
+}
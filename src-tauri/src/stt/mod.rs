@@ -0,0 +1,360 @@
+//! Speech-to-Text Engines
+//!
+//! Every STT backend implements [`SpeechToText`]. The pipeline only talks to the trait and
+//! looks engines up by id in the [`Registry`], so a new backend is one module plus one
+//! `register` line. Local Mode uses `stt.local_engine`, otherwise `stt.cloud_engine`.
+
+pub mod chunked;
+pub mod command;
+pub mod hallucination;
+pub mod in_process;
+pub mod openai;
+pub mod whisper_cli;
+pub mod whisper_cpp;
+pub mod whisper_server;
+
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+
+use crate::audio::{AudioBuffer, EncodedAudio};
+
+/// Audio handed to an engine
+pub enum AudioInput {
+    /// Decoded 16 kHz mono PCM
+    Pcm(AudioBuffer),
+    /// Compressed recording (e.g. the original WebM from the frontend)
+    Encoded(EncodedAudio),
+}
+
+impl AudioInput {
+    /// Raw samples, decoding compressed input natively
+    pub fn into_pcm(self) -> Result<AudioBuffer, String> {
+        match self {
+            AudioInput::Pcm(buffer) => Ok(buffer),
+            AudioInput::Encoded(encoded) => AudioBuffer::decode(&encoded.data, ""),
+        }
+    }
+
+    /// Compressed audio for upload, encoding PCM as Ogg/Opus
+    pub fn into_encoded(self) -> Result<EncodedAudio, String> {
+        match self {
+            AudioInput::Pcm(buffer) => buffer.encode_opus(),
+            AudioInput::Encoded(encoded) => Ok(encoded),
+        }
+    }
+}
+
+/// One transcription job
+pub struct SttRequest {
+    pub audio: AudioInput,
+    /// ISO language code, `None` = auto-detect
+    pub language: Option<String>,
+    /// Context for the recognizer (names, jargon, previous text)
+    pub prompt: Option<String>,
+}
+
+/// Engine output
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct Transcript {
+    pub text: String,
+    /// Language reported by the engine, if it detects one
+    pub language: Option<String>,
+    /// Timed pieces of the text, empty if the engine has no timestamps
+    pub segments: Vec<Segment>,
+}
+
+/// Timed piece of a transcript with the decoder's confidence, where the engine reports it
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct Segment {
+    pub start_ms: u64,
+    pub end_ms: u64,
+    pub text: String,
+    /// Mean log probability of the text tokens (0 = certain, below -1 = guessing)
+    pub avg_logprob: Option<f32>,
+    /// Probability that the segment contains no speech at all
+    pub no_speech_prob: Option<f32>,
+    /// Text length / zlib-compressed length; repetition loops score above ~2.4
+    pub compression_ratio: Option<f32>,
+}
+
+impl Segment {
+    /// Segment without confidence values; the compression ratio is computed from the text
+    pub fn new(start_ms: u64, end_ms: u64, text: String) -> Self {
+        Self {
+            start_ms,
+            end_ms,
+            compression_ratio: compression_ratio(&text),
+            text,
+            ..Self::default()
+        }
+    }
+}
+
+/// Compression ratio as computed by OpenAI Whisper (UTF-8 bytes / zlib bytes)
+pub fn compression_ratio(text: &str) -> Option<f32> {
+    use std::io::Write;
+
+    if text.trim().is_empty() {
+        return None;
+    }
+    let mut encoder = flate2::write::ZlibEncoder::new(Vec::new(), flate2::Compression::default());
+    encoder.write_all(text.as_bytes()).ok()?;
+    let compressed = encoder.finish().ok()?;
+    Some(text.len() as f32 / compressed.len() as f32)
+}
+
+/// Mean natural log of token probabilities, `None` without tokens
+pub fn avg_logprob(probabilities: impl IntoIterator<Item = f32>) -> Option<f32> {
+    let (sum, count) = probabilities
+        .into_iter()
+        .fold((0.0f32, 0usize), |(sum, count), p| {
+            (sum + p.max(f32::MIN_POSITIVE).ln(), count + 1)
+        });
+    (count > 0).then(|| sum / count as f32)
+}
+
+/// Text of all non-empty segments, space separated
+pub fn join_segments(segments: &[Segment]) -> String {
+    segments
+        .iter()
+        .map(|segment| segment.text.trim())
+        .filter(|text| !text.is_empty())
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// `hh:mm:ss,mmm` or `hh:mm:ss.mmm` (SRT, whisper.cpp) in milliseconds
+pub fn parse_timestamp(value: &str) -> Option<u64> {
+    let (clock, millis) = value.trim().split_once([',', '.'])?;
+    let mut parts = clock.split(':').map(|p| p.parse::<u64>().ok());
+    let (h, m, s) = (parts.next()??, parts.next()??, parts.next()??);
+    Some(((h * 60 + m) * 60 + s) * 1000 + millis.parse::<u64>().ok()?)
+}
+
+#[async_trait]
+pub trait SpeechToText: Send + Sync {
+    /// Shown in logs and transcript metadata
+    fn name(&self) -> String;
+
+    /// Whether audio stays on this device
+    fn is_local(&self) -> bool;
+
+    async fn transcribe(&self, request: SttRequest) -> Result<Transcript, String>;
+}
+
+/// Settings engines may need to set themselves up
+pub struct EngineContext {
+    pub whisper_path: String,
+    pub model_path: String,
+    pub in_memory_audio: bool,
+    pub config: SttConfig,
+    pub shared: SharedEngines,
+}
+
+/// Engine resources that outlive a single request (kept in `AppState`)
+#[derive(Clone, Default)]
+pub struct SharedEngines {
+    pub whisper_server: Arc<whisper_server::ServerProcess>,
+    #[cfg(feature = "whisper-rs")]
+    pub whisper_models: Arc<in_process::ModelCache>,
+}
+
+impl SharedEngines {
+    /// Stop background processes and free loaded models (app quit)
+    pub fn shutdown(&self) {
+        self.whisper_server.shutdown();
+        #[cfg(feature = "whisper-rs")]
+        self.whisper_models.shutdown();
+    }
+}
+
+pub type Factory = fn(&EngineContext) -> Result<Arc<dyn SpeechToText>, String>;
+
+/// Engine factories by id
+pub struct Registry {
+    factories: Vec<(&'static str, Factory)>,
+}
+
+impl Registry {
+    /// All engines shipped with the app
+    pub fn builtin() -> Self {
+        let mut registry = Self {
+            factories: Vec::new(),
+        };
+        registry.register("groq", openai::create_groq);
+        registry.register("openai", openai::create);
+        registry.register("whisper-cli", whisper_cli::create);
+        registry.register("whisper-server", whisper_server::create);
+        registry.register("whisper-in-process", in_process::create);
+        registry.register("command", command::create);
+        registry
+    }
+
+    /// Add an engine, replacing one with the same id
+    pub fn register(&mut self, id: &'static str, factory: Factory) {
+        self.factories.retain(|(existing, _)| *existing != id);
+        self.factories.push((id, factory));
+    }
+
+    pub fn create(
+        &self,
+        id: &str,
+        context: &EngineContext,
+    ) -> Result<Arc<dyn SpeechToText>, String> {
+        let (_, factory) = self
+            .factories
+            .iter()
+            .find(|(existing, _)| *existing == id)
+            .ok_or_else(|| format!("Unbekannte STT-Engine: {}", id))?;
+        factory(context)
+    }
+}
+
+/// STT engine selection (persisted in the backend config)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SttConfig {
+    /// Engine id used in Local Mode
+    pub local_engine: String,
+    /// Engine id used otherwise
+    pub cloud_engine: String,
+    /// Server used by the `openai` engine
+    pub openai: openai::OpenAiConfig,
+    /// Child process used by the `whisper-server` engine
+    pub whisper_server: whisper_server::WhisperServerConfig,
+    /// Threads, beam size and model unloading of the `whisper-in-process` engine
+    pub in_process: in_process::InProcessConfig,
+    /// Template and output mode of the `command` engine
+    pub command: command::CommandConfig,
+}
+
+impl Default for SttConfig {
+    fn default() -> Self {
+        Self {
+            local_engine: "whisper-cli".to_string(),
+            cloud_engine: "groq".to_string(),
+            openai: openai::OpenAiConfig::default(),
+            whisper_server: whisper_server::WhisperServerConfig::default(),
+            in_process: in_process::InProcessConfig::default(),
+            command: command::CommandConfig::default(),
+        }
+    }
+}
+
+impl SttConfig {
+    pub fn engine_id(&self, local_mode: bool) -> &str {
+        if local_mode {
+            &self.local_engine
+        } else {
+            &self.cloud_engine
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::audio::chunk::Chunk;
+
+    /// Engine that reports the length of its input instead of listening to it
+    struct FakeEngine;
+
+    #[async_trait]
+    impl SpeechToText for FakeEngine {
+        fn name(&self) -> String {
+            "fake".to_string()
+        }
+
+        fn is_local(&self) -> bool {
+            true
+        }
+
+        async fn transcribe(&self, request: SttRequest) -> Result<Transcript, String> {
+            let buffer = request.audio.into_pcm()?;
+            Ok(Transcript {
+                text: format!("{} samples", buffer.samples.len()),
+                language: request.language,
+                segments: vec![Segment::new(0, buffer.duration_ms(), String::new())],
+            })
+        }
+    }
+
+    fn create_fake(_context: &EngineContext) -> Result<Arc<dyn SpeechToText>, String> {
+        Ok(Arc::new(FakeEngine))
+    }
+
+    fn context() -> EngineContext {
+        EngineContext {
+            whisper_path: String::new(),
+            model_path: String::new(),
+            in_memory_audio: true,
+            config: SttConfig::default(),
+            shared: SharedEngines::default(),
+        }
+    }
+
+    #[test]
+    fn registry_creates_registered_engines_only() {
+        let mut registry = Registry::builtin();
+        assert!(registry.create("fake", &context()).is_err());
+
+        registry.register("fake", create_fake);
+        assert_eq!(registry.create("fake", &context()).unwrap().name(), "fake");
+        // whisper-cli refuses to start without binary and model
+        assert!(registry.create("whisper-cli", &context()).is_err());
+    }
+
+    #[test]
+    fn chunked_transcription_keeps_order_and_shifts_segments() {
+        let buffer = AudioBuffer::new(vec![0; 48_000]);
+        let chunks = [
+            Chunk {
+                range: 0..16_000,
+                overlaps_previous: false,
+            },
+            Chunk {
+                range: 16_000..48_000,
+                overlaps_previous: false,
+            },
+        ];
+
+        let transcript = tauri::async_runtime::block_on(chunked::transcribe(
+            Arc::new(FakeEngine),
+            &buffer,
+            &chunks,
+            Some("de".to_string()),
+            2,
+            |_| {},
+        ))
+        .unwrap();
+
+        assert_eq!(transcript.text, "16000 samples 32000 samples");
+        assert_eq!(transcript.language.as_deref(), Some("de"));
+        let times: Vec<_> = transcript
+            .segments
+            .iter()
+            .map(|s| (s.start_ms, s.end_ms))
+            .collect();
+        assert_eq!(times, [(0, 1000), (1000, 3000)]);
+    }
+
+    #[test]
+    fn compression_ratio_flags_repetition_loops() {
+        let normal =
+            "Bitte schick mir den Bericht bis Freitag, dann können wir ihn am Montag besprechen.";
+        let looped = "Vielen Dank. ".repeat(20);
+
+        assert!(compression_ratio(normal).unwrap() < 2.4);
+        assert!(compression_ratio(&looped).unwrap() > 2.4);
+        assert_eq!(compression_ratio("  "), None);
+    }
+
+    #[test]
+    fn avg_logprob_is_mean_log_probability() {
+        let value = avg_logprob([1.0, (-2.0f32).exp()]).unwrap();
+        assert!((value + 1.0).abs() < 1e-5);
+        assert_eq!(avg_logprob([]), None);
+    }
+}
@@ -0,0 +1,235 @@
+//! OpenAI-compatible Transcription API Client
+//!
+//! Sends audio to `{base_url}/audio/transcriptions`, the endpoint spoken by Groq, OpenAI,
+//! self-hosted faster-whisper servers, LocalAI and speaches. The `groq` engine is a fixed
+//! preset; the `openai` engine uses the server configured under `stt.openai`.
+
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use reqwest::{multipart, Client, Url};
+use serde::{Deserialize, Serialize};
+
+use super::{EngineContext, Segment, SpeechToText, SttRequest, Transcript};
+use crate::audio::EncodedAudio;
+
+/// Transcription server settings (persisted in the backend config)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct OpenAiConfig {
+    /// API root, e.g. `https://api.openai.com/v1` or `http://whisper.intranet:8000/v1`
+    pub base_url: String,
+    pub model: String,
+    /// Environment variable holding the API key, empty = server needs no key
+    pub api_key_env: String,
+    /// Key stored in the config instead, takes precedence over `api_key_env`
+    pub api_key: Option<String>,
+    /// `verbose_json` adds the detected language and segments; some servers only know `json`
+    pub response_format: String,
+}
+
+impl Default for OpenAiConfig {
+    fn default() -> Self {
+        Self {
+            base_url: "https://api.openai.com/v1".to_string(),
+            model: "whisper-1".to_string(),
+            api_key_env: "OPENAI_API_KEY".to_string(),
+            api_key: None,
+            response_format: "verbose_json".to_string(),
+        }
+    }
+}
+
+impl OpenAiConfig {
+    /// Groq Whisper v3 Turbo
+    pub fn groq() -> Self {
+        Self {
+            base_url: "https://api.groq.com/openai/v1".to_string(),
+            model: "whisper-large-v3-turbo".to_string(),
+            api_key_env: "GROQ_API_KEY".to_string(),
+            ..Self::default()
+        }
+    }
+
+    fn api_key(&self) -> Result<Option<String>, String> {
+        if let Some(key) = self.api_key.as_ref().filter(|key| !key.is_empty()) {
+            return Ok(Some(key.clone()));
+        }
+        if self.api_key_env.is_empty() {
+            return Ok(None);
+        }
+        std::env::var(&self.api_key_env)
+            .map(Some)
+            .map_err(|_| format!("{} Umgebungsvariable nicht gesetzt", self.api_key_env))
+    }
+}
+
+/// Response structure of the transcription endpoint (`json` or `verbose_json`)
+#[derive(Debug, Deserialize)]
+pub struct WhisperResponse {
+    /// Some tools only write segments; the text is then joined from them
+    #[serde(default)]
+    pub text: String,
+    #[serde(default)]
+    pub language: Option<String>,
+    #[serde(default)]
+    pub segments: Vec<WhisperSegment>,
+}
+
+/// Timed segment, in seconds; the confidence fields are only in `verbose_json`
+#[derive(Debug, Deserialize)]
+pub struct WhisperSegment {
+    pub start: f64,
+    pub end: f64,
+    pub text: String,
+    #[serde(default)]
+    pub avg_logprob: Option<f32>,
+    #[serde(default)]
+    pub no_speech_prob: Option<f32>,
+    #[serde(default)]
+    pub compression_ratio: Option<f32>,
+}
+
+impl From<WhisperResponse> for Transcript {
+    fn from(response: WhisperResponse) -> Self {
+        let to_ms = |seconds: f64| (seconds.max(0.0) * 1000.0).round() as u64;
+        let segments: Vec<Segment> = response
+            .segments
+            .into_iter()
+            .map(|segment| {
+                let text = segment.text.trim().to_string();
+                Segment {
+                    start_ms: to_ms(segment.start),
+                    end_ms: to_ms(segment.end),
+                    avg_logprob: segment.avg_logprob,
+                    no_speech_prob: segment.no_speech_prob,
+                    compression_ratio: segment
+                        .compression_ratio
+                        .or_else(|| super::compression_ratio(&text)),
+                    text,
+                }
+            })
+            .collect();
+        let text = match response.text.trim() {
+            "" => super::join_segments(&segments),
+            text => text.to_string(),
+        };
+        Self {
+            text,
+            language: response.language,
+            segments,
+        }
+    }
+}
+
+/// Engine for any OpenAI-compatible server
+pub struct OpenAiCompatible {
+    /// Provider shown in logs and metadata (`groq`, or the server's host)
+    label: String,
+    config: OpenAiConfig,
+}
+
+#[async_trait]
+impl SpeechToText for OpenAiCompatible {
+    fn name(&self) -> String {
+        format!("{}/{}", self.label, self.config.model)
+    }
+
+    /// A server on this machine (e.g. speaches on localhost) keeps audio on the device
+    fn is_local(&self) -> bool {
+        matches!(
+            host(&self.config.base_url).as_deref(),
+            Some("localhost" | "127.0.0.1" | "[::1]")
+        )
+    }
+
+    async fn transcribe(&self, request: SttRequest) -> Result<Transcript, String> {
+        let audio = request.audio.into_encoded()?;
+        upload_audio(&self.config, audio, request.language, request.prompt).await
+    }
+}
+
+/// Registry factory for the `groq` engine (needs `GROQ_API_KEY`)
+pub fn create_groq(_context: &EngineContext) -> Result<Arc<dyn SpeechToText>, String> {
+    Ok(Arc::new(OpenAiCompatible {
+        label: "groq".to_string(),
+        config: OpenAiConfig::groq(),
+    }))
+}
+
+/// Registry factory for the `openai` engine (server from `stt.openai`)
+pub fn create(context: &EngineContext) -> Result<Arc<dyn SpeechToText>, String> {
+    let config = context.config.openai.clone();
+    if config.model.is_empty() {
+        return Err("Kein Modell für den Transkriptions-Server gesetzt".to_string());
+    }
+    let label = host(&config.base_url)
+        .ok_or_else(|| format!("Ungültige Server-URL: {}", config.base_url))?;
+    Ok(Arc::new(OpenAiCompatible { label, config }))
+}
+
+fn host(base_url: &str) -> Option<String> {
+    Url::parse(base_url)
+        .ok()
+        .and_then(|url| url.host_str().map(str::to_string))
+}
+
+/// Upload encoded audio to the transcription endpoint and return the transcription
+pub async fn upload_audio(
+    config: &OpenAiConfig,
+    audio: EncodedAudio,
+    language: Option<String>,
+    prompt: Option<String>,
+) -> Result<Transcript, String> {
+    let api_key = config.api_key()?;
+
+    let client = Client::new();
+
+    // Create multipart form with audio file
+    let audio_part = multipart::Part::bytes(audio.data)
+        .file_name(audio.file_name)
+        .mime_str(audio.mime_type)
+        .map_err(|e| format!("Fehler beim Erstellen des Audio-Teils: {}", e))?;
+
+    let mut form = multipart::Form::new()
+        .text("model", config.model.clone())
+        .text("response_format", config.response_format.clone())
+        .part("file", audio_part);
+
+    // Add optional language and prompt
+    if let Some(lang) = language {
+        form = form.text("language", lang);
+    }
+    if let Some(prompt) = prompt {
+        form = form.text("prompt", prompt);
+    }
+
+    // Send request to the transcription server
+    let url = format!(
+        "{}/audio/transcriptions",
+        config.base_url.trim_end_matches('/')
+    );
+    let mut request = client.post(url).multipart(form);
+    if let Some(key) = api_key {
+        request = request.header("Authorization", format!("Bearer {}", key));
+    }
+    let response = request
+        .send()
+        .await
+        .map_err(|e| format!("Anfrage fehlgeschlagen: {}", e))?;
+
+    // Check for HTTP errors
+    if !response.status().is_success() {
+        let status = response.status();
+        let error_body = response.text().await.unwrap_or_default();
+        return Err(format!("API-Fehler {}: {}", status, error_body));
+    }
+
+    // Parse response
+    let whisper_response: WhisperResponse = response
+        .json()
+        .await
+        .map_err(|e| format!("Fehler beim Parsen der Antwort: {}", e))?;
+
+    Ok(whisper_response.into())
+}
@@ -0,0 +1,228 @@
+//! Local Whisper (whisper.cpp CLI)
+//!
+//! Runs `whisper-cli` on already decoded 16 kHz PCM. Audio never leaves the device.
+//! Results are read from its JSON output file (with token probabilities where supported),
+//! or from stdout in strict in-memory mode.
+
+use std::sync::Arc;
+
+use async_trait::async_trait;
+
+use super::{whisper_cpp, EngineContext, SpeechToText, SttRequest, Transcript};
+use crate::audio::{self, AudioBuffer};
+use crate::pipe;
+use crate::workspace::Workspace;
+
+/// whisper-cli binary and model
+#[derive(Clone)]
+pub struct WhisperCli {
+    whisper_path: String,
+    model_path: String,
+    in_memory_audio: bool,
+}
+
+impl WhisperCli {
+    pub fn new(whisper_path: &str, model_path: &str, in_memory_audio: bool) -> Self {
+        Self {
+            whisper_path: whisper_path.to_string(),
+            model_path: model_path.to_string(),
+            in_memory_audio,
+        }
+    }
+
+    /// Transcribe with whisper-cli (blocking).
+    /// The WAV is piped via stdin in strict in-memory mode, otherwise written to a workspace
+    /// of its own, so parallel runs never share a file.
+    pub fn run(
+        &self,
+        buffer: &AudioBuffer,
+        language: Option<&str>,
+        prompt: Option<&str>,
+    ) -> Result<Transcript, String> {
+        let workspace = Workspace::new(self.in_memory_audio)?;
+
+        // 1. Hand the PCM to whisper-cli as WAV (16kHz)
+        let wav_data = audio::wav::encode(buffer);
+        let input_arg = if workspace.is_in_memory() {
+            std::ffi::OsString::from("-") // whisper.cpp reads stdin for "-"
+        } else {
+            workspace.write("input.wav", &wav_data)?.into_os_string()
+        };
+
+        // 2. Run Whisper CLI
+        let command = || {
+            let mut whisper_cmd = std::process::Command::new(&self.whisper_path);
+            whisper_cmd
+                .arg("-m")
+                .arg(&self.model_path)
+                .arg("-f")
+                .arg(&input_arg)
+                .arg("-l")
+                .arg(language.unwrap_or("auto")); // Always pass language (explicit or auto)
+            if let Some(prompt) = prompt {
+                whisper_cmd.arg("--prompt").arg(prompt);
+            }
+            whisper_cmd
+        };
+        // JSON next to the input (`-of` takes the name without extension). `-ojf` adds token
+        // probabilities; builds before v1.5 only know `-oj`. Timestamp tokens stay on, or
+        // whisper.cpp returns one segment per 30 s window.
+        let json_base = if workspace.is_in_memory() {
+            None
+        } else {
+            Some(workspace.path("output")?)
+        };
+
+        let whisper_output = match &json_base {
+            // Text only on stdout; timestamps would only add noise to parse there
+            None => pipe::output_with_stdin(command().arg("--no-timestamps"), wav_data),
+            Some(base) => {
+                let run_with = |flag: &str| command().arg(flag).arg("-of").arg(base).output();
+                match run_with("-ojf") {
+                    // Some builds exit 0 after the usage text, so a missing file counts too
+                    Ok(output)
+                        if rejected_argument(&output)
+                            || (output.status.success()
+                                && !base.with_extension("json").exists()) =>
+                    {
+                        println!("ℹ️ whisper-cli has no -ojf, falling back to -oj");
+                        run_with("-oj")
+                    }
+                    result => result,
+                }
+            }
+        }
+        .map_err(|e| format!("Fehler beim Ausführen von {}: {}", self.whisper_path, e))?;
+
+        // Never parse the usage text as a transcript
+        if !whisper_output.status.success() || rejected_argument(&whisper_output) {
+            let err_msg = String::from_utf8_lossy(&whisper_output.stderr);
+            return Err(format!("Whisper CLI Fehler: {}", err_msg));
+        }
+
+        // 3. Parse the result; old builds without JSON output fall back to stdout
+        let from_json =
+            json_base.and_then(|base| std::fs::read_to_string(base.with_extension("json")).ok());
+        if let Some(content) = from_json {
+            match whisper_cpp::parse_json(&content) {
+                Ok(transcript) => return Ok(transcript),
+                Err(e) => eprintln!("⚠ {}. Using stdout.", e),
+            }
+        }
+        Ok(whisper_cpp::parse_stdout(
+            &String::from_utf8_lossy(&whisper_output.stdout),
+            &String::from_utf8_lossy(&whisper_output.stderr),
+        ))
+    }
+}
+
+/// whisper.cpp stops at arguments it does not know (`error: unknown argument: -ojf`),
+/// depending on the build with exit code 0
+fn rejected_argument(output: &std::process::Output) -> bool {
+    String::from_utf8_lossy(&output.stderr).contains("unknown argument")
+}
+
+#[async_trait]
+impl SpeechToText for WhisperCli {
+    fn name(&self) -> String {
+        let model = std::path::Path::new(&self.model_path)
+            .file_name()
+            .map(|name| name.to_string_lossy().into_owned())
+            .unwrap_or_default();
+        format!("whisper.cpp ({})", model)
+    }
+
+    fn is_local(&self) -> bool {
+        true
+    }
+
+    async fn transcribe(&self, request: SttRequest) -> Result<Transcript, String> {
+        let engine = self.clone();
+        tauri::async_runtime::spawn_blocking(move || {
+            let buffer = request.audio.into_pcm()?;
+            engine.run(
+                &buffer,
+                request.language.as_deref(),
+                request.prompt.as_deref(),
+            )
+        })
+        .await
+        .map_err(|e| e.to_string())?
+    }
+}
+
+/// Registry factory: needs the whisper-cli binary and a model from the settings
+pub fn create(context: &EngineContext) -> Result<Arc<dyn SpeechToText>, String> {
+    if context.whisper_path.is_empty() || context.model_path.is_empty() {
+        return Err("Settings error: Local Whisper binaries not configured. Please check 'Local Mode' in settings.".to_string());
+    }
+    Ok(Arc::new(WhisperCli::new(
+        &context.whisper_path,
+        &context.model_path,
+        context.in_memory_audio,
+    )))
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use std::os::unix::fs::PermissionsExt;
+
+    use super::*;
+
+    /// Stand-in for a pre-1.5 whisper-cli. `$1` says how it treats `-ojf`: `reject` prints the
+    /// usage error and exits 0, `ignore` exits 0 without output. `-oj` writes `<-of>.json`.
+    const OLD_CLI: &str = r#"#!/bin/sh
+mode="$1"; shift
+out=""
+while [ $# -gt 0 ]; do
+  case "$1" in
+    -ojf) [ "$mode" = reject ] && echo "error: unknown argument: -ojf" >&2
+          exit 0 ;;
+    -of) out="$2"; shift ;;
+  esac
+  shift
+done
+printf '{"transcription":[{"offsets":{"from":0,"to":900},"text":" Hallo."}]}' > "$out.json"
+"#;
+
+    /// Script `whisper-cli` that runs `OLD_CLI` in the given mode
+    fn old_cli(dir: &tempfile::TempDir, mode: &str) -> WhisperCli {
+        let script = dir.path().join("old-cli.sh");
+        std::fs::write(&script, OLD_CLI).unwrap();
+        let path = dir.path().join(format!("whisper-cli-{}", mode));
+        std::fs::write(
+            &path,
+            format!("#!/bin/sh\nexec sh {} {} \"$@\"\n", script.display(), mode),
+        )
+        .unwrap();
+        std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o755)).unwrap();
+        WhisperCli::new(&path.to_string_lossy(), "ggml-base.bin", false)
+    }
+
+    #[test]
+    fn falls_back_to_oj_for_builds_without_ojf() {
+        let dir = tempfile::tempdir().unwrap();
+        let buffer = AudioBuffer::new(vec![0; 16_000]);
+
+        // Rejected with exit code 0, and silently ignored (no JSON file)
+        for mode in ["reject", "ignore"] {
+            let transcript = old_cli(&dir, mode).run(&buffer, None, None).unwrap();
+            assert_eq!(transcript.text, "Hallo.", "{}", mode);
+            assert_eq!(transcript.segments[0].end_ms, 900);
+        }
+    }
+
+    #[test]
+    fn usage_text_is_never_a_transcript() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("whisper-cli");
+        let script = "#!/bin/sh\necho usage\necho 'error: unknown argument: -oj' >&2\n";
+        std::fs::write(&path, script).unwrap();
+        std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o755)).unwrap();
+
+        let cli = WhisperCli::new(&path.to_string_lossy(), "ggml-base.bin", false);
+        assert!(cli
+            .run(&AudioBuffer::new(vec![0; 16_000]), None, None)
+            .is_err());
+    }
+}
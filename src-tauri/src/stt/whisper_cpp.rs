@@ -0,0 +1,392 @@
+//! whisper.cpp Output Parsing
+//!
+//! Reads what whisper.cpp produces: the JSON file written by `whisper-cli -oj`/`-ojf`, or its
+//! stdout for old builds and strict in-memory mode (no output file allowed). With `-ojf` the
+//! token probabilities give each segment an `avg_logprob`. Depending on the build,
+//! stdout also carries log lines and bracketed timestamps. Non-speech tokens such as
+//! `[BLANK_AUDIO]` or `(music)` are removed; the language whisper detected is kept.
+
+use serde::Deserialize;
+
+use super::{avg_logprob, compression_ratio, join_segments, parse_timestamp, Segment, Transcript};
+
+/// Log prefixes some builds print to stdout instead of stderr
+const LOG_PREFIXES: &[&str] = &[
+    "whisper_",
+    "main:",
+    "system_info:",
+    "ggml_",
+    "load_backend:",
+    "output_",
+];
+
+/// `whisper-cli -oj`/`-ojf` output (fields vary between versions, everything but the text is optional)
+#[derive(Debug, Deserialize)]
+struct JsonOutput {
+    #[serde(default)]
+    params: Option<JsonLanguage>,
+    /// Since v1.5: the detected language
+    #[serde(default)]
+    result: Option<JsonLanguage>,
+    #[serde(default)]
+    transcription: Vec<JsonSegment>,
+}
+
+#[derive(Debug, Deserialize)]
+struct JsonLanguage {
+    #[serde(default)]
+    language: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct JsonSegment {
+    #[serde(default)]
+    timestamps: Option<JsonRange<String>>,
+    /// Milliseconds
+    #[serde(default)]
+    offsets: Option<JsonRange<u64>>,
+    text: String,
+    /// Only with `-ojf`
+    #[serde(default)]
+    tokens: Vec<JsonToken>,
+}
+
+#[derive(Debug, Deserialize)]
+struct JsonToken {
+    text: String,
+    p: f32,
+}
+
+#[derive(Debug, Deserialize)]
+struct JsonRange<T> {
+    from: T,
+    to: T,
+}
+
+/// Parse the `-oj` JSON file
+pub fn parse_json(content: &str) -> Result<Transcript, String> {
+    let output: JsonOutput = serde_json::from_str(content)
+        .map_err(|e| format!("Fehler beim Parsen der Whisper-Ausgabe: {}", e))?;
+
+    let segments = output
+        .transcription
+        .into_iter()
+        .map(|segment| {
+            let (start_ms, end_ms) = match (segment.offsets, segment.timestamps) {
+                (Some(offsets), _) => (offsets.from, offsets.to),
+                (None, Some(timestamps)) => (
+                    parse_timestamp(&timestamps.from).unwrap_or(0),
+                    parse_timestamp(&timestamps.to).unwrap_or(0),
+                ),
+                (None, None) => (0, 0),
+            };
+            // Control tokens (`[_BEG_]`, `[_TT_150]`) say nothing about the text
+            let probabilities = segment
+                .tokens
+                .iter()
+                .filter(|token| !token.text.starts_with("[_"))
+                .map(|token| token.p);
+            Segment {
+                avg_logprob: avg_logprob(probabilities),
+                ..Segment::new(start_ms, end_ms, segment.text.trim().to_string())
+            }
+        })
+        .collect();
+
+    // `params.language` is what was requested, so "auto" says nothing
+    let language = output
+        .result
+        .and_then(|result| result.language)
+        .or_else(|| output.params.and_then(|params| params.language))
+        .filter(|language| language != "auto");
+
+    Ok(clean(Transcript {
+        text: String::new(),
+        language,
+        segments,
+    }))
+}
+
+/// Parse stdout (with or without timestamps); the detected language is taken from stderr
+pub fn parse_stdout(stdout: &str, stderr: &str) -> Transcript {
+    let mut segments = Vec::new();
+    let mut lines = Vec::new();
+    for line in stdout.lines().map(str::trim) {
+        if line.is_empty() || LOG_PREFIXES.iter().any(|prefix| line.starts_with(prefix)) {
+            continue;
+        }
+        match timed_line(line) {
+            Some(segment) => segments.push(segment),
+            None => lines.push(line.to_string()),
+        }
+    }
+
+    let transcript = if segments.is_empty() {
+        // No timestamps (`-nt`): text only
+        Transcript {
+            text: lines
+                .iter()
+                .map(|line| remove_non_speech(line))
+                .filter(|line| !line.is_empty())
+                .collect::<Vec<_>>()
+                .join(" "),
+            ..Transcript::default()
+        }
+    } else {
+        clean(Transcript {
+            segments,
+            ..Transcript::default()
+        })
+    };
+
+    Transcript {
+        language: detected_language(stderr),
+        ..transcript
+    }
+}
+
+/// Remove non-speech tokens from every segment, drop segments left empty and rebuild the text.
+/// Engines without segments get their text cleaned instead.
+pub fn clean(transcript: Transcript) -> Transcript {
+    if transcript.segments.is_empty() {
+        return Transcript {
+            text: remove_non_speech(&transcript.text),
+            ..transcript
+        };
+    }
+
+    let segments: Vec<Segment> = transcript
+        .segments
+        .into_iter()
+        .map(|segment| {
+            let text = remove_non_speech(&segment.text);
+            if text == segment.text.trim() {
+                return Segment { text, ..segment };
+            }
+            Segment {
+                compression_ratio: compression_ratio(&text),
+                text,
+                ..segment
+            }
+        })
+        .filter(|segment| !segment.text.is_empty())
+        .collect();
+    Transcript {
+        text: join_segments(&segments),
+        language: transcript.language,
+        segments,
+    }
+}
+
+/// Text without `[...]` tags (`[BLANK_AUDIO]`, `[Musik]`) and without a sound annotation
+/// making up the whole text (`(music)`, `*laughs*`, `♪`). A `[` without its `]` is kept.
+pub fn remove_non_speech(text: &str) -> String {
+    let mut cleaned = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(open) = rest.find('[') {
+        cleaned.push_str(&rest[..open]);
+        match closing(&rest[open..], '[', ']') {
+            Some(close) => rest = &rest[open + close + 1..],
+            None => {
+                cleaned.push_str(&rest[open..]);
+                rest = "";
+            }
+        }
+    }
+    cleaned.push_str(rest);
+
+    let cleaned = cleaned.replace('♪', " ");
+    let cleaned = cleaned.split_whitespace().collect::<Vec<_>>().join(" ");
+    if is_annotation(&cleaned) {
+        String::new()
+    } else {
+        cleaned
+    }
+}
+
+/// One group spanning the whole text: `(Musik)`, but not `(1) Milch (2) Brot`;
+/// `*lacht*`, but not `*wichtig* und *dringend*`
+fn is_annotation(text: &str) -> bool {
+    if text.starts_with('(') {
+        return closing(text, '(', ')') == Some(text.len() - 1);
+    }
+    text.len() > 2
+        && text.starts_with('*')
+        && text.ends_with('*')
+        && !text[1..text.len() - 1].contains('*')
+}
+
+/// Byte index of the bracket closing the one `text` starts with
+fn closing(text: &str, open: char, close: char) -> Option<usize> {
+    let mut depth = 0usize;
+    for (i, c) in text.char_indices() {
+        if c == open {
+            depth += 1;
+        } else if c == close {
+            depth -= 1;
+            if depth == 0 {
+                return Some(i);
+            }
+        }
+    }
+    None
+}
+
+/// `[00:00:01.000 --> 00:00:03.500]  text`
+fn timed_line(line: &str) -> Option<Segment> {
+    let rest = line.strip_prefix('[')?;
+    let (range, text) = rest.split_once(']')?;
+    let (start, end) = range.split_once("-->")?;
+    Some(Segment::new(
+        parse_timestamp(start)?,
+        parse_timestamp(end)?,
+        text.trim().to_string(),
+    ))
+}
+
+/// `auto-detected language: de (p = 0.98)` from whisper's log
+fn detected_language(stderr: &str) -> Option<String> {
+    stderr.lines().find_map(|line| {
+        let (_, rest) = line.split_once("auto-detected language:")?;
+        let language = rest.split_whitespace().next()?;
+        Some(language.to_string())
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// whisper-cli v1.7 `-oj`, jfk.wav with 3 s of silence appended (base model)
+    const JSON_V1_7: &str = include_str!("fixtures/whisper-cli-1.7-oj.json");
+    /// The same clip with `-ojf`: token list per segment
+    const JSON_FULL: &str = include_str!("fixtures/whisper-cli-1.7-ojf.json");
+    /// `main` v1.4 `-oj -l en`, music intro: no `result`, language only in `params`
+    const JSON_V1_4: &str = include_str!("fixtures/main-1.4-oj.json");
+    /// Early `main -oj`: timestamps only, no offsets
+    const JSON_V1_2: &str = include_str!("fixtures/main-1.2-oj.json");
+    /// whisper-cli v1.7 stdout without and with `-nt`, and the stderr log of the `-nt` run
+    const STDOUT_TIMESTAMPS: &str = include_str!("fixtures/whisper-cli-1.7-stdout.txt");
+    const STDOUT_NO_TIMESTAMPS: &str = include_str!("fixtures/whisper-cli-1.7-stdout-nt.txt");
+    const STDERR: &str = include_str!("fixtures/whisper-cli-1.7-stderr.txt");
+
+    const JFK: &str = "And so my fellow Americans, ask not what your country can do for you, \
+                       ask what you can do for your country.";
+
+    fn times(transcript: &Transcript) -> Vec<(u64, u64)> {
+        transcript
+            .segments
+            .iter()
+            .map(|s| (s.start_ms, s.end_ms))
+            .collect()
+    }
+
+    #[test]
+    fn parses_current_json() {
+        let transcript = parse_json(JSON_V1_7).unwrap();
+
+        assert_eq!(transcript.text, JFK);
+        assert_eq!(transcript.language.as_deref(), Some("en"));
+        // `[BLANK_AUDIO]` segment dropped
+        assert_eq!(times(&transcript), [(0, 7600), (7600, 10600)]);
+    }
+
+    #[test]
+    fn full_json_has_sentence_segments_with_confidence() {
+        let transcript = parse_json(JSON_FULL).unwrap();
+
+        assert_eq!(transcript.text, JFK);
+        assert_eq!(times(&transcript), [(0, 7600), (7600, 10600)]);
+        // Not one blob per 30 s window (what `--no-timestamps` produces)
+        assert!(transcript
+            .segments
+            .iter()
+            .all(|s| s.end_ms - s.start_ms < 10_000));
+
+        for segment in &transcript.segments {
+            let logprob = segment.avg_logprob.unwrap();
+            assert!((-0.3..0.0).contains(&logprob), "{}", logprob);
+            assert!(segment.compression_ratio.is_some());
+        }
+        // Text tokens only: with `[_BEG_]` (0.94) and `[_TT_530]` (0.13) it would be -0.2
+        let second = transcript.segments[1].avg_logprob.unwrap();
+        assert!(second > -0.02, "{}", second);
+
+        // Plain `-oj` has no tokens
+        let transcript = parse_json(JSON_V1_7).unwrap();
+        assert_eq!(transcript.segments[0].avg_logprob, None);
+    }
+
+    #[test]
+    fn parses_old_json() {
+        let transcript = parse_json(JSON_V1_4).unwrap();
+        assert_eq!(transcript.text, JFK);
+        assert_eq!(transcript.language.as_deref(), Some("en"));
+        assert_eq!(times(&transcript), [(2000, 9600), (9600, 12600)]);
+
+        let transcript = parse_json(JSON_V1_2).unwrap();
+        assert_eq!(transcript.text, "Ok.");
+        assert_eq!(transcript.language, None);
+        assert_eq!(times(&transcript), [(62_500, 64_000)]);
+    }
+
+    #[test]
+    fn rejects_invalid_json() {
+        assert!(parse_json(STDOUT_TIMESTAMPS).is_err());
+    }
+
+    #[test]
+    fn parses_stdout_with_timestamps() {
+        let transcript = parse_stdout(STDOUT_TIMESTAMPS, "");
+        assert_eq!(transcript.text, JFK);
+        assert_eq!(times(&transcript), [(0, 7600), (7600, 10600)]);
+
+        // Wrappers that merge stderr into stdout: log lines are skipped
+        let merged = format!("{}{}", STDERR, STDOUT_TIMESTAMPS);
+        assert_eq!(parse_stdout(&merged, "").text, JFK);
+    }
+
+    #[test]
+    fn parses_stdout_without_timestamps() {
+        let transcript = parse_stdout(STDOUT_NO_TIMESTAMPS, STDERR);
+        assert_eq!(transcript.text, JFK);
+        assert_eq!(transcript.language.as_deref(), Some("en"));
+        assert!(transcript.segments.is_empty());
+    }
+
+    #[test]
+    fn removes_only_non_speech() {
+        assert_eq!(remove_non_speech(" [BLANK_AUDIO] "), "");
+        assert_eq!(remove_non_speech("(upbeat music)"), "");
+        assert_eq!(remove_non_speech("(Musik (leise))"), "");
+        assert_eq!(remove_non_speech("♪ ♪"), "");
+        assert_eq!(remove_non_speech("*applause*"), "");
+        assert_eq!(
+            remove_non_speech("Zweiter Satz [Musik] geht weiter."),
+            "Zweiter Satz geht weiter."
+        );
+        assert_eq!(
+            remove_non_speech("Der Wert (etwa 5 %) steigt."),
+            "Der Wert (etwa 5 %) steigt."
+        );
+        assert_eq!(remove_non_speech("3 * 4 = 12"), "3 * 4 = 12");
+    }
+
+    #[test]
+    fn keeps_dictation_that_looks_like_annotations() {
+        for text in [
+            "(1) Milch kaufen (2) Brot holen (3)",
+            "(Lacht) ja genau (Pause)",
+            "*wichtig* und *dringend*",
+            "**",
+        ] {
+            assert_eq!(remove_non_speech(text), text);
+        }
+        // Unbalanced: nothing after the bracket is lost
+        assert_eq!(
+            remove_non_speech("Liste [ erster Punkt, zweiter Punkt"),
+            "Liste [ erster Punkt, zweiter Punkt"
+        );
+        assert_eq!(remove_non_speech("a ] b [c] d"), "a ] b d");
+    }
+}
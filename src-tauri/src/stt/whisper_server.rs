@@ -0,0 +1,528 @@
+//! Local Whisper (whisper.cpp server)
+//!
+//! Keeps a `whisper-server` child process running so the model is loaded once instead of on
+//! every dictation. The process is started on demand, health-checked before each request,
+//! restarted when it crashed or the model changed, and shut down when the app quits.
+//! Audio goes to it over loopback HTTP and never leaves the device.
+
+use std::net::TcpListener;
+use std::path::{Path, PathBuf};
+use std::process::{Child, Command, Stdio};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use async_trait::async_trait;
+use reqwest::{multipart, Client};
+use serde::{Deserialize, Serialize};
+
+use super::openai::WhisperResponse;
+use super::{whisper_cpp, EngineContext, SpeechToText, SttRequest, Transcript};
+use crate::audio;
+
+/// Interval between health checks while the model loads
+const HEALTH_POLL: Duration = Duration::from_millis(250);
+
+/// whisper-server settings (persisted in the backend config)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct WhisperServerConfig {
+    /// `whisper-server` binary, empty = next to the configured whisper-cli
+    pub server_path: String,
+    /// Loopback port the server listens on
+    pub port: u16,
+    /// How long loading the model may take
+    pub startup_timeout_ms: u32,
+}
+
+impl Default for WhisperServerConfig {
+    fn default() -> Self {
+        Self {
+            server_path: String::new(),
+            port: 8178,
+            startup_timeout_ms: 60_000,
+        }
+    }
+}
+
+/// What the running process was started with; a change means a restart
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct LaunchSpec {
+    server_path: PathBuf,
+    model_path: String,
+    port: u16,
+}
+
+struct Running {
+    child: Child,
+    spec: LaunchSpec,
+}
+
+/// The shared `whisper-server` child process (one per app, kept in `AppState`)
+#[derive(Default)]
+pub struct ServerProcess {
+    running: Mutex<Option<Running>>,
+}
+
+impl ServerProcess {
+    /// Start the server if it is not running (or crashed, or runs another model)
+    fn ensure_started(&self, spec: &LaunchSpec) -> Result<(), String> {
+        let mut running = self.running.lock().map_err(|e| e.to_string())?;
+
+        if let Some(current) = running.as_mut() {
+            match current.child.try_wait() {
+                Ok(None) if current.spec == *spec => return Ok(()),
+                Ok(None) => {
+                    println!("🔁 whisper-server: settings changed, restarting");
+                    let _ = current.child.kill();
+                    let _ = current.child.wait();
+                }
+                Ok(Some(status)) => {
+                    eprintln!("⚠ whisper-server exited ({}). Restarting.", status);
+                }
+                Err(e) => eprintln!("⚠ whisper-server status unknown: {}. Restarting.", e),
+            }
+            *running = None;
+        }
+
+        // Another process on the port would answer the health checks in place of ours.
+        // Only a quick check: the port can still be taken before the child binds it, which
+        // then shows up as the child exiting (see `ready`).
+        if port_taken(spec.port) {
+            return Err(format!(
+                "Port {} ist bereits belegt, whisper-server kann nicht starten",
+                spec.port
+            ));
+        }
+
+        println!(
+            "🚀 Starting whisper-server on port {} ({})",
+            spec.port, spec.model_path
+        );
+        let child = Command::new(&spec.server_path)
+            .arg("-m")
+            .arg(&spec.model_path)
+            .arg("--host")
+            .arg("127.0.0.1")
+            .arg("--port")
+            .arg(spec.port.to_string())
+            .stdin(Stdio::null())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .spawn()
+            .map_err(|e| {
+                format!(
+                    "Fehler beim Starten von {}: {}",
+                    spec.server_path.display(),
+                    e
+                )
+            })?;
+        *running = Some(Running {
+            child,
+            spec: spec.clone(),
+        });
+        Ok(())
+    }
+
+    /// Whether the process died, e.g. while loading the model
+    fn exit_status(&self) -> Option<String> {
+        let mut running = self.running.lock().ok()?;
+        let status = running.as_mut()?.child.try_wait().ok()??;
+        *running = None;
+        Some(status.to_string())
+    }
+
+    /// Stop the server (app quit)
+    pub fn shutdown(&self) {
+        let Ok(mut running) = self.running.lock() else {
+            return;
+        };
+        if let Some(mut current) = running.take() {
+            let _ = current.child.kill();
+            let _ = current.child.wait();
+            println!("🛑 whisper-server stopped");
+        }
+    }
+}
+
+/// Engine that sends audio to the shared server process
+pub struct WhisperServer {
+    process: Arc<ServerProcess>,
+    spec: LaunchSpec,
+    startup_timeout: Duration,
+    client: Client,
+}
+
+impl WhisperServer {
+    fn base_url(&self) -> String {
+        format!("http://127.0.0.1:{}", self.spec.port)
+    }
+
+    /// Start the server if needed and wait until it has loaded the model
+    async fn ready(&self) -> Result<(), String> {
+        self.process.ensure_started(&self.spec)?;
+
+        let deadline = Instant::now() + self.startup_timeout;
+        loop {
+            // Only our own process counts as ready, not whatever took the port after it died
+            if let Some(status) = self.process.exit_status() {
+                if port_taken(self.spec.port) {
+                    return Err(format!(
+                        "whisper-server beendet ({}): Port {} ist bereits belegt",
+                        status, self.spec.port
+                    ));
+                }
+                return Err(format!("whisper-server beendet: {}", status));
+            }
+
+            // Older builds have no /health: any answer but 503 (loading) means it is up
+            let health = self
+                .client
+                .get(format!("{}/health", self.base_url()))
+                .timeout(Duration::from_secs(2))
+                .send()
+                .await;
+            match health {
+                Ok(response) if response.status() != reqwest::StatusCode::SERVICE_UNAVAILABLE => {
+                    return Ok(());
+                }
+                _ => {}
+            }
+
+            if Instant::now() >= deadline {
+                self.process.shutdown();
+                return Err("whisper-server antwortet nicht (Zeitüberschreitung)".to_string());
+            }
+            tokio::time::sleep(HEALTH_POLL).await;
+        }
+    }
+}
+
+#[async_trait]
+impl SpeechToText for WhisperServer {
+    fn name(&self) -> String {
+        let model = Path::new(&self.spec.model_path)
+            .file_name()
+            .map(|name| name.to_string_lossy().into_owned())
+            .unwrap_or_default();
+        format!("whisper-server ({})", model)
+    }
+
+    fn is_local(&self) -> bool {
+        true
+    }
+
+    async fn transcribe(&self, request: SttRequest) -> Result<Transcript, String> {
+        self.ready().await?;
+
+        let buffer = request.audio.into_pcm()?;
+        let language = request.language.unwrap_or("auto".to_string());
+        self.post(
+            &audio::wav::encode(&buffer),
+            &language,
+            request.prompt.as_deref(),
+        )
+        .await
+    }
+}
+
+impl WhisperServer {
+    /// Transcribe a WAV with `verbose_json`. Older builds only know `json`: they are asked
+    /// again, without segments and detected language then.
+    async fn post(
+        &self,
+        wav: &[u8],
+        language: &str,
+        prompt: Option<&str>,
+    ) -> Result<Transcript, String> {
+        let whisper_response = match self
+            .inference(wav, language, prompt, "verbose_json")
+            .await?
+        {
+            Ok(response) => response,
+            Err(e) => {
+                println!(
+                    "ℹ️ whisper-server rejected verbose_json ({}), falling back to json",
+                    e
+                );
+                self.inference(wav, language, prompt, "json").await??
+            }
+        };
+        Ok(whisper_cpp::clean(whisper_response.into()))
+    }
+
+    /// One `/inference` request. The inner error means the server rejected the request
+    /// (4xx) or sent a body that does not parse, which a lesser `response_format` may fix.
+    async fn inference(
+        &self,
+        wav: &[u8],
+        language: &str,
+        prompt: Option<&str>,
+        response_format: &str,
+    ) -> Result<Result<WhisperResponse, String>, String> {
+        let wav_part = multipart::Part::bytes(wav.to_vec())
+            .file_name("audio.wav")
+            .mime_str("audio/wav")
+            .map_err(|e| format!("Fehler beim Erstellen des Audio-Teils: {}", e))?;
+
+        let mut form = multipart::Form::new()
+            .text("response_format", response_format.to_string())
+            .text("language", language.to_string())
+            .part("file", wav_part);
+        if let Some(prompt) = prompt {
+            form = form.text("prompt", prompt.to_string());
+        }
+
+        let response = self
+            .client
+            .post(format!("{}/inference", self.base_url()))
+            .multipart(form)
+            .send()
+            .await
+            .map_err(|e| format!("Anfrage an whisper-server fehlgeschlagen: {}", e))?;
+
+        let status = response.status();
+        if !status.is_success() {
+            let error_body = response.text().await.unwrap_or_default();
+            let error = format!("whisper-server Fehler {}: {}", status, error_body);
+            return if status.is_client_error() {
+                Ok(Err(error))
+            } else {
+                Err(error)
+            };
+        }
+
+        Ok(response
+            .json()
+            .await
+            .map_err(|e| format!("Fehler beim Parsen der Antwort: {}", e)))
+    }
+}
+
+/// Registry factory: needs a model, and the server binary (configured or next to whisper-cli)
+pub fn create(context: &EngineContext) -> Result<Arc<dyn SpeechToText>, String> {
+    let config = &context.config.whisper_server;
+    let server_path = if config.server_path.is_empty() {
+        default_server_path(&context.whisper_path)
+    } else {
+        Some(PathBuf::from(&config.server_path))
+    };
+    let Some(server_path) = server_path.filter(|_| !context.model_path.is_empty()) else {
+        return Err("Settings error: Local Whisper binaries not configured. Please check 'Local Mode' in settings.".to_string());
+    };
+
+    Ok(Arc::new(WhisperServer {
+        process: context.shared.whisper_server.clone(),
+        spec: LaunchSpec {
+            server_path,
+            model_path: context.model_path.clone(),
+            port: config.port,
+        },
+        startup_timeout: Duration::from_millis(config.startup_timeout_ms as u64),
+        client: Client::new(),
+    }))
+}
+
+fn port_taken(port: u16) -> bool {
+    TcpListener::bind(("127.0.0.1", port)).is_err()
+}
+
+/// `whisper-server` in the directory of the whisper-cli binary
+fn default_server_path(whisper_path: &str) -> Option<PathBuf> {
+    if whisper_path.is_empty() {
+        return None;
+    }
+    let file_name = if cfg!(windows) {
+        "whisper-server.exe"
+    } else {
+        "whisper-server"
+    };
+    Some(Path::new(whisper_path).with_file_name(file_name))
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use std::io::{Read, Write};
+    use std::os::unix::fs::PermissionsExt;
+
+    use super::*;
+
+    /// Stand-in `whisper-server` running the given shell commands
+    fn fake_server(dir: &tempfile::TempDir, body: &str) -> PathBuf {
+        let path = dir.path().join("whisper-server");
+        std::fs::write(&path, format!("#!/bin/sh\n{}\n", body)).unwrap();
+        std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o755)).unwrap();
+        path
+    }
+
+    fn free_port() -> u16 {
+        TcpListener::bind(("127.0.0.1", 0))
+            .unwrap()
+            .local_addr()
+            .unwrap()
+            .port()
+    }
+
+    fn engine(process: &Arc<ServerProcess>, server_path: PathBuf, port: u16) -> WhisperServer {
+        WhisperServer {
+            process: process.clone(),
+            spec: LaunchSpec {
+                server_path,
+                model_path: "ggml-base.bin".to_string(),
+                port,
+            },
+            startup_timeout: Duration::from_secs(5),
+            client: Client::new(),
+        }
+    }
+
+    /// Answer every connection with 200 OK, like any HTTP server would on /health
+    fn answer_ok(listener: TcpListener) {
+        std::thread::spawn(move || {
+            for mut stream in listener.incoming().flatten() {
+                let _ = stream.read(&mut [0u8; 1024]);
+                let _ = stream.write_all(b"HTTP/1.1 200 OK\r\nContent-Length: 0\r\n\r\n");
+            }
+        });
+    }
+
+    fn pid(process: &ServerProcess) -> Option<u32> {
+        let running = process.running.lock().unwrap();
+        running.as_ref().map(|current| current.child.id())
+    }
+
+    #[test]
+    fn taken_port_fails_before_spawning() {
+        let dir = tempfile::tempdir().unwrap();
+        let marker = dir.path().join("started");
+        let server = fake_server(&dir, &format!("touch {}", marker.display()));
+        let listener = TcpListener::bind(("127.0.0.1", 0)).unwrap();
+        let port = listener.local_addr().unwrap().port();
+        answer_ok(listener);
+
+        let process = Arc::new(ServerProcess::default());
+        let error =
+            tauri::async_runtime::block_on(engine(&process, server, port).ready()).unwrap_err();
+        assert!(error.contains("belegt"), "{}", error);
+        assert!(!marker.exists());
+    }
+
+    #[test]
+    fn dead_child_is_not_ready_even_if_the_port_answers() {
+        let dir = tempfile::tempdir().unwrap();
+        // Still alive at the first health check, gone by the second
+        let server = fake_server(&dir, "sleep 0.05; exit 3");
+        let port = free_port();
+
+        // Something else takes the port once our process is gone
+        std::thread::spawn(move || {
+            std::thread::sleep(Duration::from_millis(150));
+            answer_ok(TcpListener::bind(("127.0.0.1", port)).unwrap());
+        });
+
+        let process = Arc::new(ServerProcess::default());
+        let error =
+            tauri::async_runtime::block_on(engine(&process, server, port).ready()).unwrap_err();
+        assert!(error.contains("beendet"), "{}", error);
+        assert!(error.contains("belegt"), "{}", error);
+        assert_eq!(pid(&process), None);
+    }
+
+    #[test]
+    fn early_exit_on_a_free_port_is_not_a_port_conflict() {
+        let dir = tempfile::tempdir().unwrap();
+        let server = fake_server(&dir, "exit 1");
+
+        let process = Arc::new(ServerProcess::default());
+        let error = tauri::async_runtime::block_on(engine(&process, server, free_port()).ready())
+            .unwrap_err();
+        assert!(error.contains("beendet"), "{}", error);
+        assert!(!error.contains("belegt"), "{}", error);
+    }
+
+    /// Answer `/inference` like an old whisper-server: 400 for `verbose_json`, text otherwise
+    fn answer_json_only(listener: TcpListener) {
+        std::thread::spawn(move || {
+            for mut stream in listener.incoming().flatten() {
+                let mut request = Vec::new();
+                let mut buf = [0u8; 4096];
+                // Read headers and the whole multipart body
+                loop {
+                    let n = stream.read(&mut buf).unwrap_or(0);
+                    request.extend_from_slice(&buf[..n]);
+                    let text = String::from_utf8_lossy(&request).to_string();
+                    let Some(head_end) = text.find("\r\n\r\n") else {
+                        if n == 0 {
+                            break;
+                        }
+                        continue;
+                    };
+                    let length = text[..head_end]
+                        .lines()
+                        .find_map(|line| {
+                            let (name, value) = line.split_once(':')?;
+                            name.eq_ignore_ascii_case("content-length")
+                                .then(|| value.trim().parse::<usize>().ok())?
+                        })
+                        .unwrap_or(0);
+                    if n == 0 || request.len() >= head_end + 4 + length {
+                        break;
+                    }
+                }
+
+                let body = if String::from_utf8_lossy(&request).contains("verbose_json") {
+                    "HTTP/1.1 400 Bad Request\r\nContent-Length: 0\r\n\r\n".to_string()
+                } else {
+                    let json = r#"{"text": " Hallo [BLANK_AUDIO] Welt"}"#;
+                    format!(
+                        "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\n\r\n{}",
+                        json.len(),
+                        json
+                    )
+                };
+                let _ = stream.write_all(body.as_bytes());
+            }
+        });
+    }
+
+    #[test]
+    fn falls_back_to_json_when_verbose_json_is_rejected() {
+        let listener = TcpListener::bind(("127.0.0.1", 0)).unwrap();
+        let port = listener.local_addr().unwrap().port();
+        answer_json_only(listener);
+
+        let process = Arc::new(ServerProcess::default());
+        let engine = engine(&process, PathBuf::from("unused"), port);
+        let wav = audio::wav::encode(&audio::AudioBuffer::new(vec![0; 1_600]));
+        let transcript = tauri::async_runtime::block_on(engine.post(&wav, "de", None)).unwrap();
+        assert_eq!(transcript.text, "Hallo Welt");
+    }
+
+    #[test]
+    fn restarts_only_when_needed_and_stops_on_shutdown() {
+        let dir = tempfile::tempdir().unwrap();
+        let server = fake_server(&dir, "exec sleep 30");
+        let process = ServerProcess::default();
+        let spec = LaunchSpec {
+            server_path: server,
+            model_path: "ggml-base.bin".to_string(),
+            port: free_port(),
+        };
+
+        process.ensure_started(&spec).unwrap();
+        let first = pid(&process).unwrap();
+        process.ensure_started(&spec).unwrap();
+        assert_eq!(pid(&process), Some(first));
+
+        // Another model: the old process is replaced
+        let other = LaunchSpec {
+            model_path: "ggml-small.bin".to_string(),
+            ..spec.clone()
+        };
+        process.ensure_started(&other).unwrap();
+        let second = pid(&process).unwrap();
+        assert_ne!(second, first);
+
+        process.shutdown();
+        assert_eq!(pid(&process), None);
+    }
+}
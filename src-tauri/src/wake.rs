@@ -0,0 +1,237 @@
+//! Wake Phrase Listener
+//!
+//! Opt-in hands-free start. A background capture keeps a short rolling buffer; only when
+//! the VAD sees an utterance end is that window transcribed, and always with the Local Mode
+//! engine (`stt.local_engine`) - wake audio never goes to the cloud, whatever mode is selected.
+//! A recognized phrase opens the HUD exactly like the hotkey.
+
+use std::sync::{mpsc, Arc, Mutex};
+use std::thread::JoinHandle;
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+use tauri::Manager;
+
+use crate::audio::endpoint::{EndpointConfig, Endpointer};
+use crate::audio::level::{AudioLevel, LevelMeter};
+use crate::audio::{capture::Recording, vad, AudioBuffer, TARGET_SAMPLE_RATE};
+use crate::config::AppConfig;
+use crate::stt::{self, AudioInput, SpeechToText, SttRequest};
+use crate::AppState;
+
+/// Pause that ends a candidate phrase
+const PHRASE_SILENCE_MS: u32 = 400;
+
+/// Longest audio window checked for a phrase
+const MAX_PHRASE_MS: u32 = 3_000;
+
+/// How often the rolling buffer is cut back while nobody speaks
+const ROLL_INTERVAL: Duration = Duration::from_millis(500);
+
+/// Wake phrase settings (persisted in the backend config)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct WakeConfig {
+    pub enabled: bool,
+    /// Matched as whole words, case-insensitively, ignoring punctuation
+    pub phrases: Vec<String>,
+}
+
+impl Default for WakeConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            phrases: vec!["Hey Capsule".to_string(), "Diktat starten".to_string()],
+        }
+    }
+}
+
+enum Event {
+    /// The VAD saw a candidate phrase end
+    Pause,
+    Stop,
+}
+
+/// Background listener, kept in `AppState`
+pub struct WakeListener {
+    event_tx: mpsc::Sender<Event>,
+    worker: JoinHandle<()>,
+}
+
+impl WakeListener {
+    /// Start listening with the configured device; transcription uses the Local Mode engine.
+    /// Blocks while the device opens, so call it off the async runtime and without the state lock.
+    pub fn start(
+        app: tauri::AppHandle,
+        config: AppConfig,
+        engines: stt::SharedEngines,
+        whisper_path: String,
+        model_path: String,
+    ) -> Result<Self, String> {
+        let engine = stt::Registry::builtin().create(
+            &config.stt.local_engine,
+            &stt::EngineContext {
+                whisper_path,
+                model_path,
+                in_memory_audio: config.in_memory_audio,
+                config: config.stt.clone(),
+                shared: engines,
+            },
+        )?;
+        if !engine.is_local() {
+            return Err(format!(
+                "Wake Phrase benötigt eine lokale STT-Engine ({} ist es nicht)",
+                engine.name()
+            ));
+        }
+
+        let (event_tx, event_rx) = mpsc::channel();
+
+        let mut splitter = Endpointer::new(EndpointConfig {
+            auto_stop: true,
+            silence_ms: PHRASE_SILENCE_MS,
+            max_duration_ms: MAX_PHRASE_MS,
+        });
+        let pause_tx = event_tx.clone();
+        let on_level = move |level: AudioLevel| {
+            if splitter.push(&level).is_some() {
+                splitter.reset();
+                let _ = pause_tx.send(Event::Pause);
+            }
+        };
+
+        let recording = Recording::start(
+            config.input_device,
+            LevelMeter::new(config.vad.clone()),
+            on_level,
+        )?;
+
+        let detector = Detector {
+            app,
+            phrases: config.wake.phrases.iter().map(|p| normalize(p)).collect(),
+            vad_config: config.vad,
+            engine,
+        };
+        let engine_name = detector.engine.name();
+        let worker = std::thread::spawn(move || detector.run(recording, event_rx));
+
+        println!("👂 Wake phrase listener started ({})", engine_name);
+        Ok(Self { event_tx, worker })
+    }
+
+    pub fn stop(self) {
+        let _ = self.event_tx.send(Event::Stop);
+        let _ = self.worker.join();
+        println!("👂 Wake phrase listener stopped");
+    }
+}
+
+struct Detector {
+    app: tauri::AppHandle,
+    phrases: Vec<String>,
+    vad_config: vad::VadConfig,
+    engine: Arc<dyn SpeechToText>,
+}
+
+impl Detector {
+    fn run(self, recording: Recording, event_rx: mpsc::Receiver<Event>) {
+        let rolling_len = (TARGET_SAMPLE_RATE * MAX_PHRASE_MS / 1000) as usize;
+
+        loop {
+            match event_rx.recv_timeout(ROLL_INTERVAL) {
+                Ok(Event::Pause) => {
+                    let window = recording.take_buffer();
+                    if let Err(e) = self.check(window) {
+                        eprintln!("⚠ Wake phrase check failed: {}", e);
+                    }
+                }
+                Err(mpsc::RecvTimeoutError::Timeout) => recording.retain_last(rolling_len),
+                Ok(Event::Stop) | Err(mpsc::RecvTimeoutError::Disconnected) => break,
+            }
+        }
+
+        recording.cancel();
+    }
+
+    /// Transcribe one candidate window locally and open the HUD on a match
+    fn check(&self, window: AudioBuffer) -> Result<(), String> {
+        // The microphone belongs to the running dictation
+        let busy = self
+            .app
+            .state::<Mutex<AppState>>()
+            .lock()
+            .map(|state| state.recording.is_some())
+            .unwrap_or(true);
+        if busy {
+            return Ok(());
+        }
+
+        let vad = vad::analyze(&window, &self.vad_config);
+        if !vad.has_speech(&self.vad_config) {
+            return Ok(());
+        }
+
+        let text = tauri::async_runtime::block_on(self.engine.transcribe(SttRequest {
+            audio: AudioInput::Pcm(window),
+            language: None,
+            prompt: None,
+        }))?
+        .text;
+
+        let heard = normalize(&text);
+        if self
+            .phrases
+            .iter()
+            .any(|phrase| contains_phrase(&heard, phrase))
+        {
+            println!("👂 Wake phrase detected: '{}'", text);
+            let hidden = self
+                .app
+                .get_webview_window("main")
+                .and_then(|window| window.is_visible().ok())
+                == Some(false);
+            if hidden {
+                crate::toggle_window(&self.app);
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Lowercase words without punctuation, single-spaced
+fn normalize(text: &str) -> String {
+    text.to_lowercase()
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Whether the normalized phrase occurs in `heard` as a run of whole words
+fn contains_phrase(heard: &str, phrase: &str) -> bool {
+    let heard: Vec<&str> = heard.split_whitespace().collect();
+    let phrase: Vec<&str> = phrase.split_whitespace().collect();
+    !phrase.is_empty() && heard.windows(phrase.len()).any(|words| words == phrase)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn matches(heard: &str, phrase: &str) -> bool {
+        contains_phrase(&normalize(heard), &normalize(phrase))
+    }
+
+    #[test]
+    fn phrases_match_whole_words_only() {
+        assert!(matches("Hey, Capsule!", "Hey Capsule"));
+        assert!(matches("Okay. Diktat starten bitte", "Diktat starten"));
+
+        // Inside longer words, or only partly said
+        assert!(!matches("They capsuled it", "Hey Capsule"));
+        assert!(!matches("Das Diktat startend", "Diktat starten"));
+        assert!(!matches("Hey", "Hey Capsule"));
+        assert!(!matches("Hey Capsule", ""));
+        assert!(!matches("", ""));
+    }
+}
@@ -0,0 +1,232 @@
+//! Watch Folder
+//!
+//! Polls a directory (e.g. a phone-sync folder) and runs every new audio file through the
+//! STT → skill pipeline, writing the result as Markdown. Sync tools write files gradually,
+//! so a file is only picked up once its size and timestamp stayed the same for one poll.
+//! Processed files are recorded in a ledger, which survives restarts.
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, UNIX_EPOCH};
+
+use serde::{Deserialize, Serialize};
+use tauri::async_runtime::JoinHandle;
+use tauri::Manager;
+
+use crate::{audio, files, PipelineSettings};
+
+const LEDGER_FILE: &str = "watch-ledger.json";
+
+/// How often the folder is scanned
+const POLL_INTERVAL: Duration = Duration::from_secs(5);
+
+/// Watch folder settings (persisted in the backend config)
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct WatchConfig {
+    pub enabled: bool,
+    pub folder: Option<PathBuf>,
+    /// Where the Markdown goes, `None` = next to the audio file
+    pub output_folder: Option<PathBuf>,
+}
+
+/// Size and modification time, to notice files that are still being written or were replaced
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+struct FileStamp {
+    size: u64,
+    modified: u64,
+}
+
+impl FileStamp {
+    fn of(path: &Path) -> Option<Self> {
+        let meta = std::fs::metadata(path).ok()?;
+        let modified = meta.modified().ok()?.duration_since(UNIX_EPOCH).ok()?;
+        Some(Self {
+            size: meta.len(),
+            modified: modified.as_secs(),
+        })
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct LedgerEntry {
+    #[serde(flatten)]
+    stamp: FileStamp,
+    /// Markdown written for the file, `None` if there was no speech
+    output: Option<PathBuf>,
+}
+
+/// Files already handled, keyed by path (`watch-ledger.json` in the app data dir)
+#[derive(Default)]
+struct Ledger {
+    path: Option<PathBuf>,
+    entries: HashMap<PathBuf, LedgerEntry>,
+}
+
+impl Ledger {
+    fn load(app: &tauri::AppHandle) -> Self {
+        let Ok(path) = app.path().app_data_dir().map(|dir| dir.join(LEDGER_FILE)) else {
+            eprintln!("⚠ App data dir not found. Processed files are not remembered.");
+            return Self::default();
+        };
+        let entries = std::fs::read_to_string(&path)
+            .ok()
+            .and_then(|content| serde_json::from_str(&content).ok())
+            .unwrap_or_default();
+        Self {
+            path: Some(path),
+            entries,
+        }
+    }
+
+    fn is_done(&self, file: &Path, stamp: FileStamp) -> bool {
+        self.entries
+            .get(file)
+            .is_some_and(|entry| entry.stamp == stamp)
+    }
+
+    fn record(&mut self, file: PathBuf, entry: LedgerEntry) {
+        self.entries.insert(file, entry);
+        let Some(path) = &self.path else { return };
+
+        let saved = path
+            .parent()
+            .map_or(Ok(()), std::fs::create_dir_all)
+            .map_err(|e| e.to_string())
+            .and_then(|_| serde_json::to_string_pretty(&self.entries).map_err(|e| e.to_string()))
+            .and_then(|json| std::fs::write(path, json).map_err(|e| e.to_string()));
+        if let Err(e) = saved {
+            eprintln!("⚠ Could not save watch ledger: {}", e);
+        }
+    }
+}
+
+/// Background watcher, kept in `AppState`
+pub struct FolderWatcher {
+    task: JoinHandle<()>,
+}
+
+impl FolderWatcher {
+    pub fn start(
+        app: tauri::AppHandle,
+        config: WatchConfig,
+        settings: PipelineSettings,
+    ) -> Result<Self, String> {
+        let folder = config
+            .folder
+            .clone()
+            .ok_or("Kein Überwachungsordner gesetzt")?;
+        if !folder.is_dir() {
+            return Err(format!("Ordner nicht gefunden: {}", folder.display()));
+        }
+
+        println!("📁 Watching folder: {}", folder.display());
+        let task = tauri::async_runtime::spawn(watch(app, folder, config.output_folder, settings));
+        Ok(Self { task })
+    }
+
+    /// Stop watching; a file in progress is picked up again next time
+    pub fn stop(self) {
+        self.task.abort();
+        println!("📁 Folder watcher stopped");
+    }
+}
+
+async fn watch(
+    app: tauri::AppHandle,
+    folder: PathBuf,
+    output_folder: Option<PathBuf>,
+    settings: PipelineSettings,
+) {
+    let mut ledger = Ledger::load(&app);
+    // Files seen in the last scan that were not processed yet
+    let mut candidates: HashMap<PathBuf, FileStamp> = HashMap::new();
+    // Not in the ledger: after a restart (e.g. network back) they are tried again
+    let mut failed: HashMap<PathBuf, FileStamp> = HashMap::new();
+
+    loop {
+        let mut seen = HashMap::new();
+        for file in scan(&folder) {
+            let Some(stamp) = FileStamp::of(&file) else {
+                continue;
+            };
+            let handled = ledger.is_done(&file, stamp) || failed.get(&file) == Some(&stamp);
+            if stamp.size == 0 || handled {
+                continue;
+            }
+
+            // Unchanged since the last scan: the sync tool is done writing it
+            if candidates.get(&file) == Some(&stamp) {
+                match process(&app, &file, output_folder.as_deref(), &settings).await {
+                    Ok(output) => ledger.record(file, LedgerEntry { stamp, output }),
+                    Err(e) => {
+                        eprintln!("❌ Watch folder: {}: {}", file.display(), e);
+                        failed.insert(file, stamp);
+                    }
+                }
+            } else {
+                seen.insert(file, stamp);
+            }
+        }
+        candidates = seen;
+
+        tokio::time::sleep(POLL_INTERVAL).await;
+    }
+}
+
+/// Supported audio files directly in the folder, sorted by name.
+/// Hidden files are skipped (sync tools use them for partial downloads).
+fn scan(folder: &Path) -> Vec<PathBuf> {
+    let is_hidden = |path: &Path| {
+        path.file_name()
+            .is_some_and(|name| name.to_string_lossy().starts_with('.'))
+    };
+    let mut files: Vec<PathBuf> = match std::fs::read_dir(folder) {
+        Ok(entries) => entries
+            .filter_map(|entry| entry.ok().map(|e| e.path()))
+            .filter(|path| path.is_file() && !is_hidden(path) && files::is_supported(path))
+            .collect(),
+        Err(e) => {
+            eprintln!("⚠ Watch folder not readable: {}", e);
+            Vec::new()
+        }
+    };
+    files.sort();
+    files
+}
+
+/// Transcribe one file and write the Markdown, `None` if it contained no speech
+async fn process(
+    app: &tauri::AppHandle,
+    file: &Path,
+    output_folder: Option<&Path>,
+    settings: &PipelineSettings,
+) -> Result<Option<PathBuf>, String> {
+    println!("📁 New file: {}", file.display());
+
+    let decode_path = file.to_path_buf();
+    let decoded =
+        tauri::async_runtime::spawn_blocking(move || audio::AudioBuffer::decode_file(&decode_path))
+            .await
+            .map_err(|e| e.to_string())??;
+
+    let engine = settings.stt_engine(app)?.name();
+    let duration_ms = decoded.duration_ms();
+    let response = crate::transcribe_and_respond(Ok(decoded), None, settings, app).await?;
+    if response.text.is_empty() {
+        println!("🔇 No speech in {}", file.display());
+        return Ok(None);
+    }
+
+    let frontmatter = files::Frontmatter::new(
+        file,
+        duration_ms,
+        engine,
+        settings,
+        response.language.as_deref(),
+    );
+    let markdown = files::render_markdown(&frontmatter, &response.text)?;
+    let saved = files::save_result(file, output_folder, &markdown)?;
+    println!("💾 Transcript saved: {}", saved.display());
+    Ok(Some(saved))
+}
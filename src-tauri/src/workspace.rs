@@ -0,0 +1,83 @@
+//! Per-Request Temp Workspace
+//!
+//! Each request gets its own private directory for intermediate audio files.
+//! The directory and everything in it is deleted when the workspace is dropped,
+//! so early returns and errors can never leave audio behind or mix up requests.
+//!
+//! In strict in-memory mode no directory exists at all and every write is refused,
+//! which makes this the single choke point guaranteeing that audio never reaches the disk.
+
+use std::io::Write;
+use std::path::PathBuf;
+
+use tempfile::TempDir;
+
+/// Private temp directory, removed on drop
+pub struct Workspace {
+    /// `None` in strict in-memory mode
+    dir: Option<TempDir>,
+}
+
+impl Workspace {
+    /// Create a uniquely named, owner-only directory in the system temp dir,
+    /// or a disk-less workspace if `in_memory` is set
+    pub fn new(in_memory: bool) -> Result<Self, String> {
+        if in_memory {
+            return Ok(Self { dir: None });
+        }
+
+        let mut builder = tempfile::Builder::new();
+        builder.prefix("voice-intelligence-");
+
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            builder.permissions(std::fs::Permissions::from_mode(0o700));
+        }
+
+        let dir = builder.tempdir().map_err(|e| {
+            format!(
+                "Temporärer Arbeitsordner konnte nicht erstellt werden: {}",
+                e
+            )
+        })?;
+        Ok(Self { dir: Some(dir) })
+    }
+
+    /// Whether writes to disk are forbidden
+    pub fn is_in_memory(&self) -> bool {
+        self.dir.is_none()
+    }
+
+    /// Write a file that only the current user can read
+    pub fn write(&self, name: &str, data: &[u8]) -> Result<PathBuf, String> {
+        let dir = self
+            .dir
+            .as_ref()
+            .ok_or("Strikter RAM-Modus: Audio darf nicht auf die Festplatte geschrieben werden")?;
+        let path = dir.path().join(name);
+        let mut options = std::fs::OpenOptions::new();
+        options.write(true).create_new(true);
+
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::OpenOptionsExt;
+            options.mode(0o600);
+        }
+
+        options
+            .open(&path)
+            .and_then(|mut file| file.write_all(data))
+            .map_err(|e| format!("Fehler beim Schreiben von {}: {}", name, e))?;
+        Ok(path)
+    }
+
+    /// Path for a file an external tool will create (e.g. its output)
+    pub fn path(&self, name: &str) -> Result<PathBuf, String> {
+        let dir = self
+            .dir
+            .as_ref()
+            .ok_or("Strikter RAM-Modus: Ausgabedateien sind nicht erlaubt")?;
+        Ok(dir.path().join(name))
+    }
+}
@@ -1,17 +1,19 @@
 import { useEffect, useRef, useState } from "react";
 import "./index.css";
-import { useAudioRecorder, RecordingState } from "./hooks/useAudioRecorder";
+import { useAudioRecorder, RecordingState, TranscriptionProgress } from "./hooks/useAudioRecorder";
 import { useAudioVisualizer } from "./hooks/useAudioVisualizer";
 import { AudioVisualizer } from "./components/AudioVisualizer";
 import { SettingsOverlay } from "./components/SettingsOverlay";
 import { Settings, X, Check } from "lucide-react";
 import { listen } from "@tauri-apps/api/event";
 import { invoke } from "@tauri-apps/api/core";
+import { getCurrentWebview } from "@tauri-apps/api/webview";
 
 function StatusDot({ state }: { state: RecordingState }) {
   const config: Record<RecordingState, { color: string; pulse: boolean }> = {
     idle: { color: "bg-white/40", pulse: false },
     recording: { color: "bg-red-500", pulse: true },
+    session: { color: "bg-violet-400", pulse: true },
     processing: { color: "bg-amber-500", pulse: true },
   };
 
@@ -22,7 +24,15 @@ function StatusDot({ state }: { state: RecordingState }) {
   );
 }
 
-function StatusLabel({ state, isPrivacyMode, isPaused }: { state: RecordingState; isPrivacyMode: boolean; isPaused: boolean }) {
+function StatusLabel({ state, isPrivacyMode, isPaused, sessionPending, progress, notice }: { state: RecordingState; isPrivacyMode: boolean; isPaused: boolean; sessionPending: number; progress: TranscriptionProgress | null; notice: string | null }) {
+  if (notice && state === "idle") {
+    return (
+      <div className="w-20 text-center">
+        <span className="text-green-400 text-sm font-medium">{notice}</span>
+      </div>
+    );
+  }
+
   if (isPaused) {
     return (
       <div className="w-20 text-center">
@@ -34,7 +44,8 @@ function StatusLabel({ state, isPrivacyMode, isPaused }: { state: RecordingState
   const labels: Record<RecordingState, string> = {
     idle: "Bereit",
     recording: "Höre zu...",
-    processing: isPrivacyMode ? "Verarbeite..." : "Verarbeite...",
+    session: sessionPending > 0 ? `Diktat (${sessionPending})` : "Diktat...",
+    processing: progress ? `Teil ${progress.done}/${progress.total}` : isPrivacyMode ? "Verarbeite..." : "Verarbeite...",
   };
 
   return (
@@ -52,6 +63,7 @@ function App() {
   const [ffmpegPath, setFfmpegPath] = useState(() => localStorage.getItem("ffmpegPath") || "");
   const [selectedLanguage, setSelectedLanguage] = useState(() => localStorage.getItem("selectedLanguage") || "auto");
   const [activeSkill, setActiveSkill] = useState(() => localStorage.getItem("activeSkill") || "auto");
+  const [isContinuous, setIsContinuous] = useState(() => localStorage.getItem("continuousMode") === "true");
 
   // Persist settings
   useEffect(() => {
@@ -74,15 +86,37 @@ function App() {
     localStorage.setItem("activeSkill", activeSkill);
   }, [activeSkill]);
 
+  useEffect(() => {
+    localStorage.setItem("continuousMode", String(isContinuous));
+  }, [isContinuous]);
+
+  // Wake phrase listener runs on the local Whisper paths; restart it when they change
+  useEffect(() => {
+    const timer = setTimeout(() => {
+      invoke("sync_wake_listener", { whisperPath, modelPath })
+        .catch((err) => console.error("Wake phrase listener:", err));
+    }, 500);
+    return () => clearTimeout(timer);
+  }, [whisperPath, modelPath]);
+
+  // Watch folder transcribes with the current STT/skill settings; restart it when they change
+  useEffect(() => {
+    const timer = setTimeout(() => {
+      invoke("sync_folder_watcher", { privacyMode: isPrivacyMode, whisperPath, modelPath, language: selectedLanguage, skill: activeSkill })
+        .catch((err) => console.error("Watch folder:", err));
+    }, 500);
+    return () => clearTimeout(timer);
+  }, [isPrivacyMode, whisperPath, modelPath, selectedLanguage, activeSkill]);
+
   // Audio Recorder Hook - now aware of privacy mode and language
-  const { state, error, stream, startRecording, stopRecording, cancelRecording, isPaused, setIsPaused } = useAudioRecorder(isPrivacyMode, whisperPath, modelPath, ffmpegPath, selectedLanguage, activeSkill);
+  const { state, error, sessionPending, progress, startRecording, stopRecording, cancelRecording, transcribeFile, isPaused, setIsPaused } = useAudioRecorder(isPrivacyMode, whisperPath, modelPath, selectedLanguage, activeSkill, isContinuous);
 
   // Sync Pause state with Settings Open state
   useEffect(() => {
     setIsPaused(isSettingsOpen);
   }, [isSettingsOpen, setIsPaused]);
 
-  const { bars, startAnalyzing, stopAnalyzing } = useAudioVisualizer();
+  const { bars } = useAudioVisualizer(state === "recording" || state === "session");
   const stateRef = useRef(state);
 
   // Keep stateRef in sync
@@ -90,6 +124,19 @@ function App() {
     stateRef.current = state;
   }, [state]);
 
+  // Push-to-talk released while start_recording was still opening the microphone:
+  // stop as soon as the recording is running instead of leaving it on behind a hidden window
+  const startingRef = useRef(false);
+  const stopPendingRef = useRef(false);
+  useEffect(() => {
+    if (state === "idle") return;
+    startingRef.current = false;
+    if (stopPendingRef.current && (state === "recording" || state === "session")) {
+      stopPendingRef.current = false;
+      stopRecording();
+    }
+  }, [state, stopRecording]);
+
   // Handle Window Resize for Settings
   useEffect(() => {
     const updateWindowSize = async () => {
@@ -102,32 +149,35 @@ function App() {
     updateWindowSize();
   }, [isSettingsOpen]);
 
-  // Start/stop visualizer when stream changes
-  useEffect(() => {
-    if (stream && state === "recording") {
-      startAnalyzing(stream);
-    } else {
-      stopAnalyzing();
-    }
-  }, [stream, state, startAnalyzing, stopAnalyzing]);
-
   // Listen for Tauri events from backend
   useEffect(() => {
     let unlistenShown: (() => void) | undefined;
     let unlistenHiding: (() => void) | undefined;
+    let unlistenAutoStop: (() => void) | undefined;
 
     const setupListeners = async () => {
       // When window is shown, start recording
       unlistenShown = await listen("window-shown", () => {
         if (stateRef.current === "idle") {
+          stopPendingRef.current = false;
+          startingRef.current = true;
           startRecording();
           // Close settings when window re-opens to be clean
           setIsSettingsOpen(false);
         }
       });
 
-      // When window is about to hide, stop recording
+      // When window is about to hide, stop recording (or end the continuous session)
       unlistenHiding = await listen("window-hiding", () => {
+        if (stateRef.current === "recording" || stateRef.current === "session") {
+          stopRecording();
+        } else if (startingRef.current) {
+          stopPendingRef.current = true;
+        }
+      });
+
+      // Backend detected end of speech (or hit the duration cap): stop and process
+      unlistenAutoStop = await listen("recording-auto-stop", () => {
         if (stateRef.current === "recording") {
           stopRecording();
         }
@@ -139,9 +189,46 @@ function App() {
     return () => {
       unlistenShown?.();
       unlistenHiding?.();
+      unlistenAutoStop?.();
     };
   }, [startRecording, stopRecording]);
 
+  // Files dropped on the HUD: transcribe with the active skill and save the result next to them
+  const [notice, setNotice] = useState<string | null>(null);
+  useEffect(() => {
+    let unlisten: (() => void) | undefined;
+    let cancelled = false;
+
+    getCurrentWebview().onDragDropEvent(async (event) => {
+      if (event.payload.type !== "drop" || stateRef.current === "processing") return;
+
+      if (stateRef.current === "recording" || stateRef.current === "session") {
+        await cancelRecording();
+      }
+      for (const path of event.payload.paths) {
+        const result = await transcribeFile(path);
+        if (result?.saved_to) {
+          console.log("💾 Saved:", result.saved_to);
+          setNotice("Gespeichert");
+        } else if (result) {
+          setNotice("Keine Sprache");
+        }
+      }
+      setTimeout(() => setNotice(null), 3000);
+    }).then((fn) => {
+      if (cancelled) {
+        fn();
+      } else {
+        unlisten = fn;
+      }
+    });
+
+    return () => {
+      cancelled = true;
+      if (unlisten) unlisten();
+    };
+  }, [cancelRecording, transcribeFile]);
+
   return (
     // justify-end keeps the capsule at the bottom when window expands
     <main className="w-full h-full flex flex-col items-center justify-end relative pb-1">
@@ -162,6 +249,8 @@ function App() {
           setSelectedLanguage={setSelectedLanguage}
           activeSkill={activeSkill}
           setActiveSkill={setActiveSkill}
+          isContinuous={isContinuous}
+          onToggleContinuous={() => setIsContinuous(!isContinuous)}
         />
       )}
 
@@ -185,7 +274,7 @@ function App() {
         {/* Audio Visualizer - handles its own visibility based on state/paused */}
         <AudioVisualizer bars={bars} state={state} isPrivacyMode={isPrivacyMode} isPaused={isPaused} />
 
-        <StatusLabel state={state} isPrivacyMode={isPrivacyMode} isPaused={isPaused} />
+        <StatusLabel state={state} isPrivacyMode={isPrivacyMode} isPaused={isPaused} sessionPending={sessionPending} progress={progress} notice={notice} />
 
         {error && (
           <span className="text-red-400 text-xs">!</span>
@@ -199,8 +288,8 @@ function App() {
           {/* Confirm/Send Button */}
           <button
             onClick={() => stopRecording()}
-            disabled={state !== "recording"}
-            className={`p-1.5 rounded-full transition-colors ${state === "recording" ? "text-white/40 hover:text-green-400" : "text-white/10 cursor-not-allowed"}`}
+            disabled={state !== "recording" && state !== "session"}
+            className={`p-1.5 rounded-full transition-colors ${state === "recording" || state === "session" ? "text-white/40 hover:text-green-400" : "text-white/10 cursor-not-allowed"}`}
             title="Finish & Send"
           >
             <Check size={16} />
@@ -209,7 +298,7 @@ function App() {
           {/* Close/Cancel Button */}
           <button
             onClick={() => {
-              if (state === "recording") {
+              if (state === "recording" || state === "session") {
                 cancelRecording();
               }
               invoke("hide_window");
@@ -11,7 +11,7 @@ interface AudioVisualizerProps {
 function AudioVisualizerComponent({ bars, state, isPrivacyMode, isPaused = false }: AudioVisualizerProps) {
     // Show visualizer if recording OR if paused (suspended state)
     // If state is 'recording' but we are 'isPaused', we show the suspended UI.
-    if (state !== 'recording' && !isPaused) {
+    if (state !== 'recording' && state !== 'session' && !isPaused) {
         return null;
     }
 
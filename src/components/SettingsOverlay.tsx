@@ -1,6 +1,40 @@
 import { X, CheckCircle2, AlertCircle, Loader2 } from "lucide-react";
 import { invoke } from "@tauri-apps/api/core";
-import { useState } from "react";
+import { useEffect, useState } from "react";
+
+interface InputDevice {
+    name: string;
+    is_default: boolean;
+    sample_rates: number[];
+}
+
+interface WatchConfig {
+    enabled: boolean;
+    folder: string | null;
+    output_folder: string | null;
+}
+
+interface OpenAiConfig {
+    base_url: string;
+    model: string;
+    api_key_env: string;
+    api_key: string | null;
+    response_format: string;
+}
+
+interface SttConfig {
+    local_engine: string;
+    cloud_engine: string;
+    openai: OpenAiConfig;
+    whisper_server: Record<string, unknown>;
+    in_process: { threads: number; beam_size: number; idle_unload_secs: number };
+}
+
+interface HallucinationConfig {
+    enabled: boolean;
+    phrases: string[];
+    [key: string]: unknown;
+}
 
 interface SettingsOverlayProps {
     isOpen: boolean;
@@ -17,6 +51,8 @@ interface SettingsOverlayProps {
     setSelectedLanguage: (lang: string) => void;
     activeSkill: string;
     setActiveSkill: (skill: string) => void;
+    isContinuous: boolean;
+    onToggleContinuous: () => void;
 }
 
 export function SettingsOverlay({
@@ -34,9 +70,223 @@ export function SettingsOverlay({
     setSelectedLanguage,
     activeSkill,
     setActiveSkill,
+    isContinuous,
+    onToggleContinuous,
 }: SettingsOverlayProps) {
     const [testStatus, setTestStatus] = useState<'idle' | 'testing' | 'success' | 'error'>('idle');
     const [testMessage, setTestMessage] = useState("");
+    const [inMemoryAudio, setInMemoryAudio] = useState(false);
+    const [inputDevices, setInputDevices] = useState<InputDevice[]>([]);
+    const [inputDevice, setInputDevice] = useState<string | null>(null);
+    const [hotkeyMode, setHotkeyMode] = useState("toggle");
+    const [endpoint, setEndpoint] = useState<Record<string, unknown>>({ auto_stop: false });
+    const [wake, setWake] = useState<{ enabled: boolean; phrases: string[] }>({ enabled: false, phrases: [] });
+    const [wakePhrases, setWakePhrases] = useState("");
+    const [wakeError, setWakeError] = useState("");
+    const [dsp, setDsp] = useState<Record<string, unknown>>({});
+    const [watch, setWatch] = useState<WatchConfig>({ enabled: false, folder: null, output_folder: null });
+    const [watchFolder, setWatchFolder] = useState("");
+    const [watchOutput, setWatchOutput] = useState("");
+    const [watchError, setWatchError] = useState("");
+    const [stt, setStt] = useState<SttConfig | null>(null);
+    const [hallucination, setHallucination] = useState<HallucinationConfig>({ enabled: true, phrases: [] });
+    const [hallucinationPhrases, setHallucinationPhrases] = useState("");
+
+    // Backend-owned settings (config.json)
+    useEffect(() => {
+        if (!isOpen) return;
+
+        invoke<{
+            in_memory_audio: boolean;
+            input_device: string | null;
+            hotkey_mode: string;
+            endpoint: Record<string, unknown>;
+            wake: { enabled: boolean; phrases: string[] };
+            dsp: Record<string, unknown>;
+            watch: WatchConfig;
+            stt: SttConfig;
+            hallucination: HallucinationConfig;
+        }>("get_config")
+            .then((config) => {
+                setInMemoryAudio(config.in_memory_audio);
+                setInputDevice(config.input_device);
+                setHotkeyMode(config.hotkey_mode);
+                setEndpoint(config.endpoint);
+                setWake(config.wake);
+                setDsp(config.dsp);
+                setWatch(config.watch);
+                setWatchFolder(config.watch.folder ?? "");
+                setWatchOutput(config.watch.output_folder ?? "");
+                setWakePhrases(config.wake.phrases.join(", "));
+                setStt(config.stt);
+                setHallucination(config.hallucination);
+                setHallucinationPhrases(config.hallucination.phrases.join("\n"));
+            })
+            .catch((err) => console.error("Failed to load config:", err));
+
+        // Re-enumerate on open so newly plugged-in microphones show up
+        invoke<InputDevice[]>("list_input_devices")
+            .then(setInputDevices)
+            .catch((err) => console.error("Failed to list input devices:", err));
+    }, [isOpen]);
+
+    // Merge a change into the backend config and persist it
+    const saveConfig = async (patch: Record<string, unknown>) => {
+        const config = await invoke<Record<string, unknown>>("get_config");
+        await invoke("update_config", { config: { ...config, ...patch } });
+    };
+
+    const handleToggleInMemoryAudio = async () => {
+        try {
+            const next = !inMemoryAudio;
+            await saveConfig({ in_memory_audio: next });
+            setInMemoryAudio(next);
+        } catch (err) {
+            console.error("Failed to save config:", err);
+        }
+    };
+
+    const handleSelectInputDevice = async (name: string) => {
+        try {
+            const next = name === "" ? null : name;
+            await saveConfig({ input_device: next });
+            setInputDevice(next);
+        } catch (err) {
+            console.error("Failed to save config:", err);
+        }
+    };
+
+    const handleSelectHotkeyMode = async (mode: string) => {
+        try {
+            await saveConfig({ hotkey_mode: mode });
+            setHotkeyMode(mode);
+        } catch (err) {
+            console.error("Failed to save config:", err);
+        }
+    };
+
+    const handleToggleAutoStop = async () => {
+        try {
+            const next = { ...endpoint, auto_stop: !endpoint.auto_stop };
+            await saveConfig({ endpoint: next });
+            setEndpoint(next);
+        } catch (err) {
+            console.error("Failed to save config:", err);
+        }
+    };
+
+    const handleUpdateEndpoint = async (patch: Record<string, unknown>) => {
+        try {
+            const next = { ...endpoint, ...patch };
+            await saveConfig({ endpoint: next });
+            setEndpoint(next);
+        } catch (err) {
+            console.error("Failed to save config:", err);
+        }
+    };
+
+    const handleUpdateDsp = async (patch: Record<string, unknown>) => {
+        try {
+            const next = { ...dsp, ...patch };
+            await saveConfig({ dsp: next });
+            setDsp(next);
+        } catch (err) {
+            console.error("Failed to save config:", err);
+        }
+    };
+
+    const handleUpdateStt = async (patch: Partial<SttConfig>) => {
+        if (!stt) return;
+        try {
+            const next = { ...stt, ...patch };
+            await saveConfig({ stt: next });
+            setStt(next);
+        } catch (err) {
+            console.error("Failed to save config:", err);
+            return;
+        }
+        // The wake listener keeps the engine it was started with; restart it on the new one
+        if (wake.enabled) {
+            try {
+                setWakeError("");
+                await invoke("sync_wake_listener", { whisperPath, modelPath });
+            } catch (err) {
+                console.error("Failed to update wake phrase:", err);
+                setWakeError(String(err));
+            }
+        }
+    };
+
+    const handleUpdateOpenAi = (patch: Partial<OpenAiConfig>) => {
+        if (!stt) return;
+        handleUpdateStt({ openai: { ...stt.openai, ...patch } });
+    };
+
+    const saveHallucination = async (next: HallucinationConfig) => {
+        try {
+            await saveConfig({ hallucination: next });
+            setHallucination(next);
+        } catch (err) {
+            console.error("Failed to save config:", err);
+        }
+    };
+
+    // One phrase per line (phrases may contain commas)
+    const handleSaveHallucinationPhrases = () => {
+        const phrases = hallucinationPhrases.split("\n").map((p) => p.trim()).filter((p) => p !== "");
+        if (phrases.join("\n") !== hallucination.phrases.join("\n")) {
+            saveHallucination({ ...hallucination, phrases });
+        }
+    };
+
+    // Persist wake settings and restart the background listener with them
+    const saveWake = async (next: { enabled: boolean; phrases: string[] }) => {
+        try {
+            await saveConfig({ wake: next });
+            setWake(next);
+            setWakeError("");
+            await invoke("sync_wake_listener", { whisperPath, modelPath });
+        } catch (err) {
+            console.error("Failed to update wake phrase:", err);
+            setWakeError(String(err));
+        }
+    };
+
+    const handleToggleWake = () => saveWake({ ...wake, enabled: !wake.enabled });
+
+    const handleSaveWakePhrases = () => {
+        const phrases = wakePhrases.split(",").map((p) => p.trim()).filter((p) => p !== "");
+        if (phrases.join(",") !== wake.phrases.join(",")) {
+            saveWake({ ...wake, phrases });
+        }
+    };
+
+    // Persist watch folder settings and restart the watcher with them
+    const saveWatch = async (next: WatchConfig) => {
+        try {
+            await saveConfig({ watch: next });
+            setWatch(next);
+            setWatchError("");
+            await invoke("sync_folder_watcher", {
+                privacyMode: isPrivacyMode,
+                whisperPath,
+                modelPath,
+                language: selectedLanguage,
+                skill: activeSkill,
+            });
+        } catch (err) {
+            console.error("Failed to update watch folder:", err);
+            setWatchError(String(err));
+        }
+    };
+
+    const handleSaveWatchPaths = () => {
+        const folder = watchFolder.trim() || null;
+        const output_folder = watchOutput.trim() || null;
+        if (folder !== watch.folder || output_folder !== watch.output_folder) {
+            saveWatch({ ...watch, folder, output_folder });
+        }
+    };
 
     const handleTestConnection = async () => {
         setTestStatus('testing');
@@ -67,6 +317,274 @@ export function SettingsOverlay({
                     <X size={14} />
                 </button>
             </div>
+            <div className="flex items-center justify-between">
+                <div className="flex flex-col">
+                    <span className="text-sm font-medium text-white">Microphone</span>
+                    <span className="text-[10px] text-white/40">Input device</span>
+                </div>
+                <select
+                    value={inputDevice ?? ""}
+                    onChange={(e) => handleSelectInputDevice(e.target.value)}
+                    className="bg-zinc-700 border border-white/10 rounded px-2 py-1 text-xs text-white focus:outline-none focus:border-violet-500/50 w-32"
+                >
+                    <option value="">System Default</option>
+                    {inputDevices.map((device) => (
+                        <option key={device.name} value={device.name}>
+                            {device.is_default ? `${device.name} (Default)` : device.name}
+                        </option>
+                    ))}
+                    {/* Saved device is currently unplugged: capture falls back to the default */}
+                    {inputDevice && !inputDevices.some((d) => d.name === inputDevice) && (
+                        <option value={inputDevice}>{inputDevice} (Not connected)</option>
+                    )}
+                </select>
+            </div>
+
+            <div className="flex items-center justify-between">
+                <div className="flex flex-col">
+                    <span className="text-sm font-medium text-white">Hotkey (Alt+Space)</span>
+                    <span className="text-[10px] text-white/40">Hold to talk, tap to toggle</span>
+                </div>
+                <select
+                    value={hotkeyMode}
+                    onChange={(e) => handleSelectHotkeyMode(e.target.value)}
+                    className="bg-zinc-700 border border-white/10 rounded px-2 py-1 text-xs text-white focus:outline-none focus:border-violet-500/50 w-32"
+                >
+                    <option value="toggle">Toggle</option>
+                    <option value="push_to_talk">Push-to-Talk</option>
+                </select>
+            </div>
+
+            <div className="flex items-center justify-between">
+                <div className="flex flex-col">
+                    <span className="text-sm font-medium text-white">Continuous Dictation</span>
+                    <span className="text-[10px] text-white/40">Types each sentence as you pause</span>
+                </div>
+                <button
+                    onClick={onToggleContinuous}
+                    className={`relative w-10 h-6 rounded-full transition-colors duration-200 focus:outline-none ${isContinuous ? "bg-violet-500" : "bg-zinc-700"
+                        }`}
+                >
+                    <span
+                        className={`absolute top-1 left-1 bg-white w-4 h-4 rounded-full shadow-sm transition-transform duration-200 ${isContinuous ? "translate-x-4" : "translate-x-0"
+                            }`}
+                    />
+                </button>
+            </div>
+
+            <div className="flex items-center justify-between">
+                <div className="flex flex-col">
+                    <span className="text-sm font-medium text-white">Auto-Stop</span>
+                    <span className="text-[10px] text-white/40">Stop after a pause in speech</span>
+                </div>
+
+            <div className="flex gap-2">
+                {Boolean(endpoint.auto_stop) && (
+                    <div className="flex-1 space-y-1 animate-in fade-in slide-in-from-top-1 duration-200">
+                        <label className="text-[10px] text-white/60 uppercase font-semibold">Pause (s)</label>
+                        <input
+                            key={`silence-${endpoint.silence_ms}`}
+                            type="number"
+                            min={0.3}
+                            step={0.1}
+                            defaultValue={Number(endpoint.silence_ms ?? 1200) / 1000}
+                            onBlur={(e) => handleUpdateEndpoint({ silence_ms: Math.round((Number(e.target.value) || 1.2) * 1000) })}
+                            className="w-full bg-black/40 border border-white/10 rounded px-2 py-1 text-xs text-white focus:outline-none focus:border-violet-500/50"
+                        />
+                    </div>
+                )}
+                <div className="flex-1 space-y-1">
+                    <label className="text-[10px] text-white/60 uppercase font-semibold">Max Length (min, 0 = ∞)</label>
+                    <input
+                        key={`max-${endpoint.max_duration_ms}`}
+                        type="number"
+                        min={0}
+                        defaultValue={Number(endpoint.max_duration_ms ?? 0) / 60000}
+                        onBlur={(e) => handleUpdateEndpoint({ max_duration_ms: Math.round((Number(e.target.value) || 0) * 60000) })}
+                        className="w-full bg-black/40 border border-white/10 rounded px-2 py-1 text-xs text-white focus:outline-none focus:border-violet-500/50"
+                    />
+                </div>
+            </div>
+                <button
+                    onClick={handleToggleAutoStop}
+                    className={`relative w-10 h-6 rounded-full transition-colors duration-200 focus:outline-none ${endpoint.auto_stop ? "bg-violet-500" : "bg-zinc-700"
+                        }`}
+                >
+                    <span
+                        className={`absolute top-1 left-1 bg-white w-4 h-4 rounded-full shadow-sm transition-transform duration-200 ${endpoint.auto_stop ? "translate-x-4" : "translate-x-0"
+                            }`}
+                    />
+                </button>
+            </div>
+
+            <div className="flex items-center justify-between">
+                <div className="flex flex-col">
+                    <span className="text-sm font-medium text-white">High-Pass Filter</span>
+                    <span className="text-[10px] text-white/40">Removes hum and rumble below 80 Hz</span>
+                </div>
+                <button
+                    onClick={() => handleUpdateDsp({ high_pass: !dsp.high_pass })}
+                    className={`relative w-10 h-6 rounded-full transition-colors duration-200 focus:outline-none ${dsp.high_pass ? "bg-violet-500" : "bg-zinc-700"
+                        }`}
+                >
+                    <span
+                        className={`absolute top-1 left-1 bg-white w-4 h-4 rounded-full shadow-sm transition-transform duration-200 ${dsp.high_pass ? "translate-x-4" : "translate-x-0"
+                            }`}
+                    />
+                </button>
+            </div>
+
+            <div className="flex items-center justify-between">
+                <div className="flex flex-col">
+                    <span className="text-sm font-medium text-white">Noise Suppression</span>
+                    <span className="text-[10px] text-white/40">Filters steady fan and office noise</span>
+                </div>
+                <button
+                    onClick={() => handleUpdateDsp({ noise_suppression: !dsp.noise_suppression })}
+                    className={`relative w-10 h-6 rounded-full transition-colors duration-200 focus:outline-none ${dsp.noise_suppression ? "bg-violet-500" : "bg-zinc-700"
+                        }`}
+                >
+                    <span
+                        className={`absolute top-1 left-1 bg-white w-4 h-4 rounded-full shadow-sm transition-transform duration-200 ${dsp.noise_suppression ? "translate-x-4" : "translate-x-0"
+                            }`}
+                    />
+                </button>
+            </div>
+
+            <div className="flex items-center justify-between">
+                <div className="flex flex-col">
+                    <span className="text-sm font-medium text-white">Normalize</span>
+                    <span className="text-[10px] text-white/40">Even out quiet and loud speakers</span>
+                </div>
+                <select
+                    value={String(dsp.normalize ?? "off")}
+                    onChange={(e) => handleUpdateDsp({ normalize: e.target.value })}
+                    className="bg-zinc-700 border border-white/10 rounded px-2 py-1 text-xs text-white focus:outline-none focus:border-violet-500/50 w-32"
+                >
+                    <option value="off">Off</option>
+                    <option value="peak">Peak</option>
+                    <option value="loudness">Loudness (LUFS)</option>
+                </select>
+            </div>
+
+            <div className="flex items-center justify-between">
+                <div className="flex flex-col">
+                    <span className="text-sm font-medium text-white">Save Processed Audio</span>
+                    <span className="text-[10px] text-white/40">Keeps original + cleaned WAV to compare</span>
+                </div>
+                <button
+                    onClick={() => handleUpdateDsp({ save_processed: !dsp.save_processed })}
+                    className={`relative w-10 h-6 rounded-full transition-colors duration-200 focus:outline-none ${dsp.save_processed ? "bg-violet-500" : "bg-zinc-700"
+                        }`}
+                >
+                    <span
+                        className={`absolute top-1 left-1 bg-white w-4 h-4 rounded-full shadow-sm transition-transform duration-200 ${dsp.save_processed ? "translate-x-4" : "translate-x-0"
+                            }`}
+                    />
+                </button>
+            </div>
+
+            <div className="flex items-center justify-between">
+                <div className="flex flex-col">
+                    <span className="text-sm font-medium text-white">Hallucination Filter</span>
+                    <span className="text-[10px] text-white/40">Drops silence artifacts and repetition loops</span>
+                </div>
+                <button
+                    onClick={() => saveHallucination({ ...hallucination, enabled: !hallucination.enabled })}
+                    className={`relative w-10 h-6 rounded-full transition-colors duration-200 focus:outline-none ${hallucination.enabled ? "bg-violet-500" : "bg-zinc-700"
+                        }`}
+                >
+                    <span
+                        className={`absolute top-1 left-1 bg-white w-4 h-4 rounded-full shadow-sm transition-transform duration-200 ${hallucination.enabled ? "translate-x-4" : "translate-x-0"
+                            }`}
+                    />
+                </button>
+            </div>
+
+            {hallucination.enabled && (
+                <div className="space-y-1 animate-in fade-in slide-in-from-top-1 duration-200">
+                    <label className="text-[10px] text-white/60 uppercase font-semibold">Phrases (short clips only, one per line)</label>
+                    <textarea
+                        value={hallucinationPhrases}
+                        onChange={(e) => setHallucinationPhrases(e.target.value)}
+                        onBlur={handleSaveHallucinationPhrases}
+                        rows={4}
+                        placeholder="Vielen Dank."
+                        className="w-full bg-black/40 border border-white/10 rounded px-2 py-1 text-xs text-white placeholder-white/20 focus:outline-none focus:border-violet-500/50 resize-none"
+                    />
+                </div>
+            )}
+
+            <div className="flex items-center justify-between">
+                <div className="flex flex-col">
+                    <span className="text-sm font-medium text-white">Wake Phrase</span>
+                    <span className="text-[10px] text-white/40">Always local Whisper, never sent to the cloud</span>
+                </div>
+                <button
+                    onClick={handleToggleWake}
+                    className={`relative w-10 h-6 rounded-full transition-colors duration-200 focus:outline-none ${wake.enabled ? "bg-violet-500" : "bg-zinc-700"
+                        }`}
+                >
+                    <span
+                        className={`absolute top-1 left-1 bg-white w-4 h-4 rounded-full shadow-sm transition-transform duration-200 ${wake.enabled ? "translate-x-4" : "translate-x-0"
+                            }`}
+                    />
+                </button>
+            </div>
+
+            {wake.enabled && (
+                <div className="space-y-1 animate-in fade-in slide-in-from-top-1 duration-200">
+                    <input
+                        type="text"
+                        value={wakePhrases}
+                        onChange={(e) => setWakePhrases(e.target.value)}
+                        onBlur={handleSaveWakePhrases}
+                        placeholder="Hey Capsule, Diktat starten"
+                        className="w-full bg-black/40 border border-white/10 rounded px-2 py-1 text-xs text-white placeholder-white/20 focus:outline-none focus:border-violet-500/50"
+                    />
+                    {wakeError && <div className="text-[10px] text-red-400">{wakeError}</div>}
+                </div>
+            )}
+
+            <div className="flex items-center justify-between">
+                <div className="flex flex-col">
+                    <span className="text-sm font-medium text-white">Watch Folder</span>
+                    <span className="text-[10px] text-white/40">New audio files → Markdown</span>
+                </div>
+                <button
+                    onClick={() => saveWatch({ ...watch, enabled: !watch.enabled })}
+                    className={`relative w-10 h-6 rounded-full transition-colors duration-200 focus:outline-none ${watch.enabled ? "bg-violet-500" : "bg-zinc-700"
+                        }`}
+                >
+                    <span
+                        className={`absolute top-1 left-1 bg-white w-4 h-4 rounded-full shadow-sm transition-transform duration-200 ${watch.enabled ? "translate-x-4" : "translate-x-0"
+                            }`}
+                    />
+                </button>
+            </div>
+
+            {watch.enabled && (
+                <div className="space-y-1 animate-in fade-in slide-in-from-top-1 duration-200">
+                    <input
+                        type="text"
+                        value={watchFolder}
+                        onChange={(e) => setWatchFolder(e.target.value)}
+                        onBlur={handleSaveWatchPaths}
+                        placeholder="C:\Users\me\Voice Memos"
+                        className="w-full bg-black/40 border border-white/10 rounded px-2 py-1 text-xs text-white placeholder-white/20 focus:outline-none focus:border-violet-500/50"
+                    />
+                    <input
+                        type="text"
+                        value={watchOutput}
+                        onChange={(e) => setWatchOutput(e.target.value)}
+                        onBlur={handleSaveWatchPaths}
+                        placeholder="Output folder (optional, default: next to file)"
+                        className="w-full bg-black/40 border border-white/10 rounded px-2 py-1 text-xs text-white placeholder-white/20 focus:outline-none focus:border-violet-500/50"
+                    />
+                    {watchError && <div className="text-[10px] text-red-400">{watchError}</div>}
+                </div>
+            )}
+
             <div className="flex items-center justify-between">
                 <div className="flex flex-col">
                     <span className="text-sm font-medium text-white">Language / Sprache</span>
@@ -102,6 +620,49 @@ export function SettingsOverlay({
                 </select>
             </div>
 
+            {!isPrivacyMode && stt && (
+                <div className="flex items-center justify-between">
+                    <div className="flex flex-col">
+                        <span className="text-sm font-medium text-white">Cloud STT</span>
+                        <span className="text-[10px] text-white/40">Transcription server</span>
+                    </div>
+                    <select
+                        value={stt.cloud_engine}
+                        onChange={(e) => handleUpdateStt({ cloud_engine: e.target.value })}
+                        className="bg-zinc-700 border border-white/10 rounded px-2 py-1 text-xs text-white focus:outline-none focus:border-violet-500/50 w-32"
+                    >
+                        <option value="groq">Groq</option>
+                        <option value="openai">OpenAI-compatible</option>
+                    </select>
+                </div>
+            )}
+
+            {!isPrivacyMode && stt?.cloud_engine === "openai" && (
+                <div className="space-y-1 animate-in fade-in slide-in-from-top-1 duration-200">
+                    <input
+                        type="text"
+                        defaultValue={stt.openai.base_url}
+                        onBlur={(e) => handleUpdateOpenAi({ base_url: e.target.value.trim() })}
+                        placeholder="https://api.openai.com/v1"
+                        className="w-full bg-black/40 border border-white/10 rounded px-2 py-1 text-xs text-white placeholder-white/20 focus:outline-none focus:border-violet-500/50"
+                    />
+                    <input
+                        type="text"
+                        defaultValue={stt.openai.model}
+                        onBlur={(e) => handleUpdateOpenAi({ model: e.target.value.trim() })}
+                        placeholder="Model (e.g. whisper-1)"
+                        className="w-full bg-black/40 border border-white/10 rounded px-2 py-1 text-xs text-white placeholder-white/20 focus:outline-none focus:border-violet-500/50"
+                    />
+                    <input
+                        type="text"
+                        defaultValue={stt.openai.api_key_env}
+                        onBlur={(e) => handleUpdateOpenAi({ api_key_env: e.target.value.trim() })}
+                        placeholder="API key env variable (empty = no key)"
+                        className="w-full bg-black/40 border border-white/10 rounded px-2 py-1 text-xs text-white placeholder-white/20 focus:outline-none focus:border-violet-500/50"
+                    />
+                </div>
+            )}
+
             <div className="h-px bg-white/10 my-1" />
 
             <div className="flex items-center justify-between">
@@ -129,6 +690,50 @@ export function SettingsOverlay({
                         🛡️ Local Mode active. Data stays on device.
                     </div>
 
+                    {stt && (
+                        <div className="flex items-center justify-between">
+                            <div className="flex flex-col">
+                                <span className="text-xs font-medium text-white">Local Engine</span>
+                                <span className="text-[10px] text-white/40">Server keeps the model loaded</span>
+                            </div>
+                            <select
+                                value={stt.local_engine}
+                                onChange={(e) => handleUpdateStt({ local_engine: e.target.value })}
+                                className="bg-zinc-700 border border-white/10 rounded px-2 py-1 text-xs text-white focus:outline-none focus:border-violet-500/50 w-32"
+                            >
+                                <option value="whisper-cli">whisper-cli</option>
+                                <option value="whisper-server">whisper-server</option>
+                                <option value="whisper-in-process">In-Process</option>
+                                <option value="command">Custom Command</option>
+                            </select>
+                        </div>
+                    )}
+
+                    {stt?.local_engine === "whisper-in-process" && (
+                        <div className="flex gap-2 animate-in fade-in slide-in-from-top-1 duration-200">
+                            <div className="flex-1 space-y-1">
+                                <label className="text-[10px] text-white/60 uppercase font-semibold">Threads (0 = auto)</label>
+                                <input
+                                    type="number"
+                                    min={0}
+                                    defaultValue={stt.in_process.threads}
+                                    onBlur={(e) => handleUpdateStt({ in_process: { ...stt.in_process, threads: Number(e.target.value) || 0 } })}
+                                    className="w-full bg-black/40 border border-white/10 rounded px-2 py-1 text-xs text-white focus:outline-none focus:border-violet-500/50"
+                                />
+                            </div>
+                            <div className="flex-1 space-y-1">
+                                <label className="text-[10px] text-white/60 uppercase font-semibold">Beam Size</label>
+                                <input
+                                    type="number"
+                                    min={1}
+                                    defaultValue={stt.in_process.beam_size}
+                                    onBlur={(e) => handleUpdateStt({ in_process: { ...stt.in_process, beam_size: Number(e.target.value) || 1 } })}
+                                    className="w-full bg-black/40 border border-white/10 rounded px-2 py-1 text-xs text-white focus:outline-none focus:border-violet-500/50"
+                                />
+                            </div>
+                        </div>
+                    )}
+
                     <div className="space-y-1">
                         <label className="text-[10px] text-white/60 uppercase font-semibold">Local Whisper Binary Path</label>
                         <input
@@ -162,7 +767,22 @@ export function SettingsOverlay({
                         />
                     </div>
 
-
+                    <div className="flex items-center justify-between">
+                        <div className="flex flex-col">
+                            <span className="text-xs font-medium text-white">Strict RAM Mode</span>
+                            <span className="text-[10px] text-white/40">Audio is piped, never written to disk.</span>
+                        </div>
+                        <button
+                            onClick={handleToggleInMemoryAudio}
+                            className={`relative w-10 h-6 rounded-full transition-colors duration-200 focus:outline-none ${inMemoryAudio ? "bg-violet-500" : "bg-zinc-700"
+                                }`}
+                        >
+                            <span
+                                className={`absolute top-1 left-1 bg-white w-4 h-4 rounded-full shadow-sm transition-transform duration-200 ${inMemoryAudio ? "translate-x-4" : "translate-x-0"
+                                    }`}
+                            />
+                        </button>
+                    </div>
 
                     <button
                         onClick={handleTestConnection}
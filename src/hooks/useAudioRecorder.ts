@@ -1,34 +1,50 @@
 import { useState, useRef, useCallback, useEffect } from 'react';
 import { invoke } from '@tauri-apps/api/core';
+import { listen } from '@tauri-apps/api/event';
 
-export type RecordingState = 'idle' | 'recording' | 'processing';
+export type RecordingState = 'idle' | 'recording' | 'session' | 'processing';
+
+/** Result of an imported file (`transcribe_file`) */
+export interface FileTranscription {
+    text: string;
+    saved_to: string | null;
+}
+
+/** Long recordings: chunks transcribed so far */
+export interface TranscriptionProgress {
+    done: number;
+    total: number;
+}
 
 interface AudioRecorderResult {
     state: RecordingState;
     error: string | null;
-    stream: MediaStream | null;
+    sessionPending: number; // Continuous mode: utterances not yet typed
+    progress: TranscriptionProgress | null; // Chunked transcription, null for short recordings
     startRecording: () => Promise<void>;
     stopRecording: () => Promise<void>;
     cancelRecording: () => Promise<void>;
+    transcribeFile: (path: string) => Promise<FileTranscription | null>;
     isPaused: boolean;
     setIsPaused: (paused: boolean) => void;
 }
 
+/**
+ * Drives the native capture in the Rust backend.
+ * Audio never passes through the webview - this hook only tracks state.
+ */
 export function useAudioRecorder(
     isPrivacyMode: boolean,
     currentWhisperPath: string,
     currentModelPath: string,
-    currentFfmpegPath: string,
     selectedLanguage: string,
-    activeSkill: string // New parameter
+    activeSkill: string, // New parameter
+    isContinuous: boolean
 ): AudioRecorderResult {
     const [state, setState] = useState<RecordingState>('idle');
     const [error, setError] = useState<string | null>(null);
-    const [stream, setStream] = useState<MediaStream | null>(null);
-    const mediaRecorderRef = useRef<MediaRecorder | null>(null);
-    const chunksRef = useRef<Blob[]>([]);
-    const streamRef = useRef<MediaStream | null>(null);
-    const startTimeRef = useRef<number>(0);
+    const [sessionPending, setSessionPending] = useState(0);
+    const [progress, setProgress] = useState<TranscriptionProgress | null>(null);
 
     const [isPaused, setIsPaused] = useState(false);
 
@@ -36,9 +52,9 @@ export function useAudioRecorder(
     const privacyModeRef = useRef(isPrivacyMode);
     const whisperPathRef = useRef(currentWhisperPath);
     const modelPathRef = useRef(currentModelPath);
-    const ffmpegPathRef = useRef(currentFfmpegPath);
     const selectedLanguageRef = useRef(selectedLanguage);
     const activeSkillRef = useRef(activeSkill);
+    const continuousRef = useRef(isContinuous);
     const isPausedRef = useRef(isPaused);
 
     // Keep refs in sync
@@ -54,10 +70,6 @@ export function useAudioRecorder(
         modelPathRef.current = currentModelPath;
     }, [currentModelPath]);
 
-    useEffect(() => {
-        ffmpegPathRef.current = currentFfmpegPath;
-    }, [currentFfmpegPath]);
-
     useEffect(() => {
         selectedLanguageRef.current = selectedLanguage;
     }, [selectedLanguage]);
@@ -66,190 +78,161 @@ export function useAudioRecorder(
         activeSkillRef.current = activeSkill;
     }, [activeSkill]);
 
+    useEffect(() => {
+        continuousRef.current = isContinuous;
+    }, [isContinuous]);
+
     useEffect(() => {
         isPausedRef.current = isPaused;
     }, [isPaused]);
 
+    // Continuous mode progress from the backend
+    useEffect(() => {
+        let unlisten: (() => void) | undefined;
+        let cancelled = false;
+
+        listen<{ active: boolean; pending: number }>('session-state', (event) => {
+            setSessionPending(event.payload.pending);
+        }).then((fn) => {
+            if (cancelled) {
+                fn();
+            } else {
+                unlisten = fn;
+            }
+        });
 
-    const isCancelledRef = useRef(false);
+        return () => {
+            cancelled = true;
+            if (unlisten) unlisten();
+        };
+    }, []);
 
-    // ... (keep refs in sync) ...
+    // Chunk progress of long recordings
+    useEffect(() => {
+        let unlisten: (() => void) | undefined;
+        let cancelled = false;
+
+        listen<TranscriptionProgress>('transcription-progress', (event) => {
+            setProgress(event.payload);
+        }).then((fn) => {
+            if (cancelled) {
+                fn();
+            } else {
+                unlisten = fn;
+            }
+        });
+
+        return () => {
+            cancelled = true;
+            if (unlisten) unlisten();
+        };
+    }, []);
+
+    // STT/LLM settings for the backend pipeline
+    const pipelineSettings = () => ({
+        privacyMode: privacyModeRef.current,
+        whisperPath: whisperPathRef.current,
+        modelPath: modelPathRef.current,
+        language: selectedLanguageRef.current,
+        skill: activeSkillRef.current // Pass active skill
+    });
 
     const startRecording = useCallback(async () => {
         if (state !== 'idle') {
-
             return;
         }
 
         try {
             setError(null);
-            isCancelledRef.current = false; // Reset cancel flag
-
-            // Ensure previous recording is fully cleaned up
-            if (streamRef.current) {
-                streamRef.current.getTracks().forEach(track => track.stop());
-                streamRef.current = null;
+            if (continuousRef.current) {
+                // Backend splits at pauses and types each utterance as it finishes
+                await invoke('start_session', pipelineSettings());
+                setState('session');
+            } else {
+                await invoke('start_recording');
+                setState('recording');
             }
-            // Reset other refs
-            setStream(null);
-            mediaRecorderRef.current = null;
-            chunksRef.current = [];
-            startTimeRef.current = 0;
-
-
-
-            const mediaStream = await navigator.mediaDevices.getUserMedia({
-                audio: {
-                    echoCancellation: true,
-                    noiseSuppression: true,
-                    sampleRate: 16000,
-                }
-            });
-            streamRef.current = mediaStream;
-            setStream(mediaStream);
-
-            const mediaRecorder = new MediaRecorder(mediaStream, {
-                mimeType: 'audio/webm;codecs=opus',
-            });
-
-            mediaRecorder.ondataavailable = (event) => {
-                if (event.data.size > 0 && !isPausedRef.current) {
-                    chunksRef.current.push(event.data);
-                }
-            };
-
-            mediaRecorder.onstop = async () => {
-                // If paused or cancelled, discard audio
-                if (isPausedRef.current || isCancelledRef.current) {
-
-
-                    // Cleanup
-                    if (streamRef.current) {
-                        streamRef.current.getTracks().forEach(track => track.stop());
-                        streamRef.current = null;
-                    }
-                    setStream(null);
-                    mediaRecorderRef.current = null;
-                    chunksRef.current = [];
-                    startTimeRef.current = 0;
-                    setState('idle');
-                    return;
-                }
-
-                // Calculate recording duration
-                const durationMs = Date.now() - startTimeRef.current;
-                const currentPrivacyMode = privacyModeRef.current;
-                const currentWhisperPath = whisperPathRef.current;
-                const currentModelPath = modelPathRef.current;
-                const currentSelectedLanguage = selectedLanguageRef.current;
-                const currentActiveSkill = activeSkillRef.current;
-
-
-
-
-                setState('processing');
-
-                // Copy chunks before cleanup
-                const chunks = [...chunksRef.current];
-
-                if (chunks.length === 0) {
-
-                    // cleanup inline
-                    if (streamRef.current) {
-                        streamRef.current.getTracks().forEach(track => track.stop());
-                        streamRef.current = null;
-                    }
-                    setStream(null);
-                    mediaRecorderRef.current = null;
-                    chunksRef.current = [];
-                    startTimeRef.current = 0;
-
-                    setState('idle');
-                    return;
-                }
-
-                // Combine all chunks into single blob
-                const audioBlob = new Blob(chunks, { type: 'audio/webm' });
-
-
-                const arrayBuffer = await audioBlob.arrayBuffer();
-                const audioData = Array.from(new Uint8Array(arrayBuffer));
-
-                // Cleanup before sending
-                if (streamRef.current) {
-                    streamRef.current.getTracks().forEach(track => track.stop());
-                    streamRef.current = null;
-                }
-                setStream(null);
-                mediaRecorderRef.current = null;
-                chunksRef.current = [];
-                startTimeRef.current = 0;
-
-                try {
-                    // Send to Rust backend with duration and privacy mode
-                    await invoke<string>('process_audio', {
-                        audioData,
-                        durationMs,
-                        privacyMode: currentPrivacyMode,
-                        whisperPath: currentWhisperPath,
-                        modelPath: currentModelPath,
-                        ffmpegPath: ffmpegPathRef.current,
-                        language: currentSelectedLanguage,
-                        skill: currentActiveSkill // Pass active skill
-                    });
-                } catch (err) {
-                    console.error('❌ Process audio error:', err);
-                    setError(err instanceof Error ? err.message : String(err));
-                }
-
-                setState('idle');
-            };
-
-            mediaRecorderRef.current = mediaRecorder;
-            startTimeRef.current = Date.now();
-            mediaRecorder.start(); // Collect all data in one single chunk at the end
-            setState('recording');
-
         } catch (err) {
             console.error('❌ Microphone error:', err);
-            setError(err instanceof Error ? err.message : 'Mikrofon-Zugriff fehlgeschlagen');
+            setError(err instanceof Error ? err.message : String(err));
             setState('idle');
         }
     }, [state]);
 
     const stopRecording = useCallback(async () => {
-        if (mediaRecorderRef.current && state === 'recording') {
+        if (state !== 'recording' && state !== 'session') {
+            return;
+        }
+
+        setState('processing');
 
-            mediaRecorderRef.current.stop();
+        try {
+            if (state === 'session') {
+                // Resolves once the last utterance is typed
+                await invoke('stop_session', { discard: false });
+            } else {
+                // Backend stops capture and runs STT + LLM on the recorded PCM
+                await invoke<string>('stop_recording', pipelineSettings());
+            }
+        } catch (err) {
+            console.error('❌ Process audio error:', err);
+            setError(err instanceof Error ? err.message : String(err));
         }
+
+        setProgress(null);
+        setState('idle');
     }, [state]);
 
     const cancelRecording = useCallback(async () => {
-        if (mediaRecorderRef.current && state === 'recording') {
+        if (state !== 'recording' && state !== 'session') {
+            return;
+        }
 
-            isCancelledRef.current = true;
-            mediaRecorderRef.current.stop();
+        try {
+            if (state === 'session') {
+                // Utterances already spoken are still typed, only the current one is dropped
+                await invoke('stop_session', { discard: true });
+            } else {
+                await invoke('cancel_recording');
+            }
+        } catch (err) {
+            console.error('❌ Cancel error:', err);
         }
+        setState('idle');
     }, [state]);
 
+    // Imported file: same pipeline, result is saved next to the file instead of typed
+    const transcribeFile = useCallback(async (path: string) => {
+        setState('processing');
+        setError(null);
+
+        let result: FileTranscription | null = null;
+        try {
+            result = await invoke<FileTranscription>('transcribe_file', { path, save: true, ...pipelineSettings() });
+        } catch (err) {
+            console.error('❌ File transcription error:', err);
+            setError(err instanceof Error ? err.message : String(err));
+        }
+
+        setProgress(null);
+        setState('idle');
+        return result;
+    }, []);
+
     const setPaused = useCallback((paused: boolean) => {
         setIsPaused(paused);
         isPausedRef.current = paused;
 
         if (paused) {
-            // Stop recording immediately to release microphone
-            if (mediaRecorderRef.current && mediaRecorderRef.current.state === 'recording') {
-
-                mediaRecorderRef.current.stop();
-            }
+            // Discard the running recording immediately to release the microphone
+            cancelRecording();
         } else {
             // Resume recording if we are effectively idle (and not processing)
-
             setTimeout(() => {
                 startRecording();
             }, 100);
         }
-    }, [state, startRecording]);
+    }, [cancelRecording, startRecording]);
 
-    return { state, error, stream, startRecording, stopRecording, cancelRecording, isPaused, setIsPaused: setPaused };
+    return { state, error, sessionPending, progress, startRecording, stopRecording, cancelRecording, transcribeFile, isPaused, setIsPaused: setPaused };
 }
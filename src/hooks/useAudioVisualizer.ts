@@ -1,98 +1,67 @@
-import { useState, useRef, useEffect, useCallback } from 'react';
+import { useState, useEffect } from 'react';
+import { listen } from '@tauri-apps/api/event';
 
 interface AudioVisualizerResult {
     bars: number[];  // Normalized values 0-1 for each bar
-    startAnalyzing: (stream: MediaStream) => void;
-    stopAnalyzing: () => void;
+    isSpeech: boolean;
 }
 
-const NUM_BARS = 5;
-const FFT_SIZE = 256;
-
-export function useAudioVisualizer(): AudioVisualizerResult {
-    const [bars, setBars] = useState<number[]>(new Array(NUM_BARS).fill(0));
-    const audioContextRef = useRef<AudioContext | null>(null);
-    const analyserRef = useRef<AnalyserNode | null>(null);
-    const sourceRef = useRef<MediaStreamAudioSourceNode | null>(null);
-    const animationFrameRef = useRef<number | null>(null);
-    const isRunningRef = useRef(false);
-
-    const analyze = useCallback(() => {
-        if (!analyserRef.current || !isRunningRef.current) return;
+/** Payload of the backend `audio-level` event (values 0-1 of full scale) */
+interface AudioLevel {
+    rms: number;
+    peak: number;
+    is_speech: boolean;
+}
 
-        const analyser = analyserRef.current;
-        const dataArray = new Uint8Array(analyser.frequencyBinCount);
-        analyser.getByteFrequencyData(dataArray);
+const NUM_BARS = 5;
 
-        // Sample from different frequency ranges for visual variety
-        const step = Math.floor(dataArray.length / (NUM_BARS + 1));
-        const newBars: number[] = [];
+// Center bars react stronger than the outer ones for a capsule-like shape
+const BAR_WEIGHTS = [0.55, 0.8, 1, 0.8, 0.55];
 
-        for (let i = 0; i < NUM_BARS; i++) {
-            // Take average of a small range for smoother visualization
-            const startIdx = (i + 1) * step - 2;
-            const endIdx = (i + 1) * step + 2;
-            let sum = 0;
-            let count = 0;
+// Levels below this are shown as silence
+const MIN_DB = -60;
 
-            for (let j = Math.max(0, startIdx); j < Math.min(dataArray.length, endIdx); j++) {
-                sum += dataArray[j];
-                count++;
-            }
+/**
+ * Turns the live `audio-level` events of the native capture into visualizer bars.
+ * Only listens while `active` is set.
+ */
+export function useAudioVisualizer(active: boolean): AudioVisualizerResult {
+    const [bars, setBars] = useState<number[]>(new Array(NUM_BARS).fill(0));
+    const [isSpeech, setIsSpeech] = useState(false);
 
-            // Normalize to 0-1 with some amplification for visual effect
-            const normalized = Math.min(1, (sum / count / 255) * 1.5);
-            newBars.push(normalized);
+    useEffect(() => {
+        if (!active) {
+            setBars(new Array(NUM_BARS).fill(0));
+            setIsSpeech(false);
+            return;
         }
 
-        setBars(newBars);
-        animationFrameRef.current = requestAnimationFrame(analyze);
-    }, []);
-
-    const startAnalyzing = useCallback((stream: MediaStream) => {
-        // Create audio context
-        audioContextRef.current = new AudioContext();
-        analyserRef.current = audioContextRef.current.createAnalyser();
-        analyserRef.current.fftSize = FFT_SIZE;
-        analyserRef.current.smoothingTimeConstant = 0.7;
+        let unlisten: (() => void) | undefined;
+        let cancelled = false;
 
-        // Connect stream to analyser
-        sourceRef.current = audioContextRef.current.createMediaStreamSource(stream);
-        sourceRef.current.connect(analyserRef.current);
+        listen<AudioLevel>('audio-level', (event) => {
+            const { rms, is_speech } = event.payload;
 
-        // Start animation loop
-        isRunningRef.current = true;
-        analyze();
-    }, [analyze]);
+            // Map to a dB scale so quiet speech still moves the bars
+            const db = 20 * Math.log10(Math.max(rms, 1e-6));
+            const level = Math.min(1, Math.max(0, (db - MIN_DB) / -MIN_DB));
 
-    const stopAnalyzing = useCallback(() => {
-        isRunningRef.current = false;
-
-        if (animationFrameRef.current) {
-            cancelAnimationFrame(animationFrameRef.current);
-            animationFrameRef.current = null;
-        }
-
-        if (sourceRef.current) {
-            sourceRef.current.disconnect();
-            sourceRef.current = null;
-        }
-
-        if (audioContextRef.current) {
-            audioContextRef.current.close();
-            audioContextRef.current = null;
-        }
-
-        analyserRef.current = null;
-        setBars(new Array(NUM_BARS).fill(0));
-    }, []);
+            // Smooth between events
+            setBars((prev) => prev.map((old, i) => old * 0.4 + level * BAR_WEIGHTS[i] * 0.6));
+            setIsSpeech(is_speech);
+        }).then((fn) => {
+            if (cancelled) {
+                fn();
+            } else {
+                unlisten = fn;
+            }
+        });
 
-    // Cleanup on unmount
-    useEffect(() => {
         return () => {
-            stopAnalyzing();
+            cancelled = true;
+            if (unlisten) unlisten();
         };
-    }, [stopAnalyzing]);
+    }, [active]);
 
-    return { bars, startAnalyzing, stopAnalyzing };
+    return { bars, isSpeech };
 }